@@ -7,9 +7,9 @@
 // modified, or distributed except according to those terms.
 
 use self::git2_builder::Empty;
+use anyhow::Result;
 #[cfg(test)]
 use anyhow::anyhow;
-use anyhow::{Error, Result};
 use bon::Builder;
 use git2_rs::{
     BranchType, Commit, DescribeFormatOptions, DescribeOptions, Reference, Repository,
@@ -19,18 +19,11 @@ use std::{
     env,
     path::{Path, PathBuf},
 };
-use time::{
-    OffsetDateTime, UtcOffset,
-    format_description::{self, well_known::Iso8601},
-};
+use time::OffsetDateTime;
 use vergen_lib::{
     AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, Describe,
-    Dirty, Sha, VergenKey, add_default_map_entry, add_map_entry,
-    constants::{
-        GIT_BRANCH_NAME, GIT_COMMIT_AUTHOR_EMAIL, GIT_COMMIT_AUTHOR_NAME, GIT_COMMIT_COUNT,
-        GIT_COMMIT_DATE_NAME, GIT_COMMIT_MESSAGE, GIT_COMMIT_TIMESTAMP_NAME,
-        GIT_COMMIT_TIMESTAMP_UNIX_NAME, GIT_DESCRIBE_NAME, GIT_DIRTY_NAME, GIT_SHA_NAME,
-    },
+    Dirty, EntryContext, Error, FromManifest, ManifestKeys, Sha, TimeFormat, TimeZone, VERGEN_TZ,
+    VergenKey, add_default_map_entry, add_map_entry, git_stamps, handle_key_failure, in_time_zone,
 };
 #[cfg(feature = "allow_remote")]
 use {
@@ -246,6 +239,15 @@ pub struct Git2 {
     /// Enable local offset date/timestamp output
     #[builder(default = false)]
     use_local: bool,
+    /// Render the date/timestamp output in the given time zone.  Takes precedence over
+    /// `use_local`, and is overridden by the `VERGEN_TZ` environment variable.
+    time_zone: Option<TimeZone>,
+    /// The format of the `VERGEN_GIT_COMMIT_DATE` output, `[year]-[month]-[day]` by default
+    #[builder(default = TimeFormat::Date)]
+    commit_date_format: TimeFormat,
+    /// The format of the `VERGEN_GIT_COMMIT_TIMESTAMP` output, ISO 8601 by default
+    #[builder(default = TimeFormat::Iso8601)]
+    commit_timestamp_format: TimeFormat,
     #[cfg(test)]
     /// Fail
     #[builder(default = false)]
@@ -304,12 +306,14 @@ impl Git2 {
     #[cfg(not(test))]
     fn add_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
     ) -> Result<()> {
         self.inner_add_entries(
+            context,
             idempotent,
             cargo_rustc_env,
             cargo_rerun_if_changed,
@@ -320,6 +324,7 @@ impl Git2 {
     #[cfg(test)]
     fn add_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
@@ -329,6 +334,7 @@ impl Git2 {
             return Err(anyhow!("failed to create entries"));
         }
         self.inner_add_entries(
+            context,
             idempotent,
             cargo_rustc_env,
             cargo_rerun_if_changed,
@@ -364,7 +370,7 @@ impl Git2 {
         repo_dir: &PathBuf,
         _warnings: &mut CargoWarning,
     ) -> Result<Repository> {
-        Repository::discover(repo_dir).map_err(Into::into)
+        Repository::discover(repo_dir).map_err(|e| Error::not_a_git_repo(e).into())
     }
 
     #[cfg(feature = "allow_remote")]
@@ -412,10 +418,11 @@ impl Git2 {
             ));
             Ok(repo)
         } else {
-            Err(anyhow::anyhow!(
+            Err(Error::not_a_git_repo(format!(
                 "Could not find a git repository at '{}'",
                 repo_dir.display()
             ))
+            .into())
         }
     }
 
@@ -441,6 +448,7 @@ impl Git2 {
     #[allow(clippy::too_many_lines)]
     fn inner_add_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
@@ -461,21 +469,33 @@ impl Git2 {
         }
 
         if self.branch {
-            if let Ok(_value) = env::var(GIT_BRANCH_NAME) {
+            if context.env_override(VergenKey::GitBranch).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitBranch,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                Self::add_branch_name(idempotent, false, &repo, cargo_rustc_env, cargo_warning)?;
+                Self::add_branch_name(
+                    context,
+                    idempotent,
+                    false,
+                    &repo,
+                    cargo_rustc_env,
+                    cargo_warning,
+                )?;
             }
         }
 
         if self.commit_author_email {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_EMAIL) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorEmail)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env,
@@ -483,6 +503,7 @@ impl Git2 {
                 );
             } else {
                 Self::add_opt_value(
+                    context,
                     idempotent,
                     commit.author().email().ok(),
                     VergenKey::GitCommitAuthorEmail,
@@ -493,8 +514,12 @@ impl Git2 {
         }
 
         if self.commit_author_name {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_NAME) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorName)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env,
@@ -502,6 +527,7 @@ impl Git2 {
                 );
             } else {
                 Self::add_opt_value(
+                    context,
                     idempotent,
                     commit.author().name().ok(),
                     VergenKey::GitCommitAuthorName,
@@ -512,23 +538,38 @@ impl Git2 {
         }
 
         if self.commit_count {
-            if let Ok(_value) = env::var(GIT_COMMIT_COUNT) {
+            if context.env_override(VergenKey::GitCommitCount).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitCount,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                Self::add_commit_count(idempotent, false, &repo, cargo_rustc_env, cargo_warning);
+                Self::add_commit_count(
+                    context,
+                    idempotent,
+                    false,
+                    &repo,
+                    cargo_rustc_env,
+                    cargo_warning,
+                );
             }
         }
 
-        self.add_git_timestamp_entries(&commit, idempotent, cargo_rustc_env, cargo_warning)?;
+        self.add_git_timestamp_entries(
+            context,
+            &commit,
+            idempotent,
+            cargo_rustc_env,
+            cargo_warning,
+        )?;
 
         if self.commit_message {
-            if let Ok(_value) = env::var(GIT_COMMIT_MESSAGE) {
+            if context.env_override(VergenKey::GitCommitMessage).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env,
@@ -536,6 +577,7 @@ impl Git2 {
                 );
             } else {
                 Self::add_opt_value(
+                    context,
                     idempotent,
                     commit.message().ok(),
                     VergenKey::GitCommitMessage,
@@ -546,8 +588,9 @@ impl Git2 {
         }
 
         if let Some(sha) = self.sha {
-            if let Ok(_value) = env::var(GIT_SHA_NAME) {
+            if context.env_override(VergenKey::GitSha).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitSha,
                     cargo_rustc_env,
@@ -556,6 +599,7 @@ impl Git2 {
             } else if sha.short() {
                 let obj = repo.revparse_single("HEAD")?;
                 Self::add_opt_value(
+                    context,
                     idempotent,
                     obj.short_id()?.as_str().ok(),
                     VergenKey::GitSha,
@@ -568,8 +612,9 @@ impl Git2 {
         }
 
         if let Some(dirty) = self.dirty {
-            if let Ok(_value) = env::var(GIT_DIRTY_NAME) {
+            if context.env_override(VergenKey::GitDirty).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDirty,
                     cargo_rustc_env,
@@ -595,8 +640,9 @@ impl Git2 {
         }
 
         if let Some(describe) = self.describe {
-            if let Ok(_value) = env::var(GIT_DESCRIBE_NAME) {
+            if context.env_override(VergenKey::GitDescribe).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDescribe,
                     cargo_rustc_env,
@@ -620,10 +666,21 @@ impl Git2 {
                     _ = describe_opts.pattern(pattern);
                 }
 
-                let describe = repo
+                match repo
                     .describe(&describe_opts)
-                    .map(|x| x.format(Some(&format_opts)).map_err(Error::from))??;
-                add_map_entry(VergenKey::GitDescribe, describe, cargo_rustc_env);
+                    .and_then(|x| x.format(Some(&format_opts)))
+                {
+                    Ok(describe) => {
+                        add_map_entry(VergenKey::GitDescribe, describe, cargo_rustc_env);
+                    }
+                    Err(e) => handle_key_failure(
+                        context,
+                        VergenKey::GitDescribe,
+                        Error::git_command_failed(e),
+                        cargo_rustc_env,
+                        cargo_warning,
+                    )?,
+                }
             }
         }
 
@@ -659,6 +716,7 @@ impl Git2 {
     }
 
     fn add_branch_name(
+        context: &EntryContext,
         idempotent: bool,
         add_default: bool,
         repo: &Repository,
@@ -668,6 +726,7 @@ impl Git2 {
         if repo.head_detached()? {
             if add_default {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitBranch,
                     cargo_rustc_env,
@@ -690,6 +749,7 @@ impl Git2 {
             }
             if !found_head {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitBranch,
                     cargo_rustc_env,
@@ -701,6 +761,7 @@ impl Git2 {
     }
 
     fn add_opt_value(
+        context: &EntryContext,
         idempotent: bool,
         value: Option<&str>,
         key: VergenKey,
@@ -710,11 +771,12 @@ impl Git2 {
         if let Some(val) = value {
             add_map_entry(key, val, cargo_rustc_env);
         } else {
-            add_default_map_entry(idempotent, key, cargo_rustc_env, cargo_warning);
+            add_default_map_entry(context, idempotent, key, cargo_rustc_env, cargo_warning);
         }
     }
 
     fn add_commit_count(
+        context: &EntryContext,
         idempotent: bool,
         add_default: bool,
         repo: &Repository,
@@ -729,11 +791,12 @@ impl Git2 {
             add_map_entry(key, revwalk.count().to_string(), cargo_rustc_env);
             return;
         }
-        add_default_map_entry(idempotent, key, cargo_rustc_env, cargo_warning);
+        add_default_map_entry(context, idempotent, key, cargo_rustc_env, cargo_warning);
     }
 
     fn add_git_timestamp_entries(
         &self,
+        context: &EntryContext,
         commit: &Commit<'_>,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -745,41 +808,57 @@ impl Git2 {
         // timestamp (see issue #452).
         let ts = self.compute_local_offset(commit)?;
 
-        if let Ok(_value) = env::var(GIT_COMMIT_DATE_NAME) {
+        if context.env_override(VergenKey::GitCommitDate).is_some() {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitDate,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_date_entry(idempotent, &ts, cargo_rustc_env, cargo_warning)?;
+            self.add_git_date_entry(context, idempotent, &ts, cargo_rustc_env, cargo_warning)?;
         }
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestamp)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestamp,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_timestamp_entry(idempotent, &ts, cargo_rustc_env, cargo_warning)?;
+            self.add_git_timestamp_entry(context, idempotent, &ts, cargo_rustc_env, cargo_warning)?;
         }
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_UNIX_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestampUnix)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestampUnix,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_timestamp_unix_entry(idempotent, &ts, cargo_rustc_env, cargo_warning);
+            self.add_git_timestamp_unix_entry(
+                context,
+                idempotent,
+                &ts,
+                cargo_rustc_env,
+                cargo_warning,
+            );
         }
         Ok(())
     }
 
     fn add_git_timestamp_unix_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -788,6 +867,7 @@ impl Git2 {
         if self.commit_timestamp_unix {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env,
@@ -807,17 +887,16 @@ impl Git2 {
     // this in not included in coverage, because on *nix the local offset is always unsafe
     fn compute_local_offset(&self, commit: &Commit<'_>) -> Result<OffsetDateTime> {
         let no_offset = OffsetDateTime::from_unix_timestamp(commit.time().seconds())?;
-        if self.use_local {
-            let local = UtcOffset::local_offset_at(no_offset)?;
-            let local_offset = no_offset.checked_to_offset(local).unwrap_or(no_offset);
-            Ok(local_offset)
-        } else {
-            Ok(no_offset)
-        }
+        let zone = self
+            .time_zone
+            .as_ref()
+            .or(self.use_local.then_some(&TimeZone::Local));
+        Ok(in_time_zone(no_offset, zone)?)
     }
 
     fn add_git_date_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -826,16 +905,16 @@ impl Git2 {
         if self.commit_date {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitDate,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                let format = format_description::parse_borrowed::<1>("[year]-[month]-[day]")?;
                 add_map_entry(
                     VergenKey::GitCommitDate,
-                    ts.format(&format)?,
+                    self.commit_date_format.format(ts)?,
                     cargo_rustc_env,
                 );
             }
@@ -845,6 +924,7 @@ impl Git2 {
 
     fn add_git_timestamp_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -853,6 +933,7 @@ impl Git2 {
         if self.commit_timestamp {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env,
@@ -861,7 +942,7 @@ impl Git2 {
             } else {
                 add_map_entry(
                     VergenKey::GitCommitTimestamp,
-                    ts.format(&Iso8601::DEFAULT)?,
+                    self.commit_timestamp_format.format(ts)?,
                     cargo_rustc_env,
                 );
             }
@@ -887,21 +968,75 @@ impl Git2 {
     }
 }
 
+impl FromManifest for Git2 {
+    const SECTION: &'static str = "git";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "branch",
+            "commit_author_name",
+            "commit_author_email",
+            "commit_count",
+            "commit_message",
+            "commit_date",
+            "commit_timestamp",
+            "commit_timestamp_unix",
+            "describe",
+            "describe_tags",
+            "describe_dirty",
+            "sha",
+            "sha_short",
+            "dirty",
+            "dirty_include_untracked",
+            "use_local",
+        ])?;
+        let mut git2 = Self::builder()
+            .branch(keys.has("branch"))
+            .commit_author_name(keys.has("commit_author_name"))
+            .commit_author_email(keys.has("commit_author_email"))
+            .commit_count(keys.has("commit_count"))
+            .commit_message(keys.has("commit_message"))
+            .commit_date(keys.has("commit_date"))
+            .commit_timestamp(keys.has("commit_timestamp"))
+            .commit_timestamp_unix(keys.contains("commit_timestamp_unix"))
+            .use_local(keys.contains("use_local"))
+            .build();
+        git2.describe = keys.has("describe").then(|| {
+            Describe::builder()
+                .tags(keys.contains("describe_tags"))
+                .dirty(keys.contains("describe_dirty"))
+                .build()
+        });
+        git2.sha = keys
+            .has("sha")
+            .then(|| Sha::builder().short(keys.contains("sha_short")).build());
+        git2.dirty = keys.has("dirty").then(|| {
+            Dirty::builder()
+                .include_untracked(keys.contains("dirty_include_untracked"))
+                .build()
+        });
+        Ok(git2)
+    }
+}
+
 impl AddEntries for Git2 {
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.any() {
             self.add_entries(
+                context,
                 idempotent,
                 cargo_rustc_env,
                 cargo_rerun_if_changed,
                 cargo_warning,
-            )?;
+            )
+            .map_err(|e| Error::downcast_or(e.into(), Error::git_command_failed))?;
         }
         Ok(())
     }
@@ -910,13 +1045,13 @@ impl AddEntries for Git2 {
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             // Clear any previous warnings.  This should be it.
             cargo_warning.clear();
@@ -930,6 +1065,7 @@ impl AddEntries for Git2 {
 
             if self.branch {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitBranch,
                     cargo_rustc_env_map,
@@ -938,6 +1074,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_author_email {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env_map,
@@ -946,6 +1083,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_author_name {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env_map,
@@ -954,6 +1092,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_count {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitCount,
                     cargo_rustc_env_map,
@@ -962,6 +1101,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_date {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitDate,
                     cargo_rustc_env_map,
@@ -970,6 +1110,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_message {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env_map,
@@ -978,6 +1119,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_timestamp {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env_map,
@@ -986,6 +1128,7 @@ impl AddEntries for Git2 {
             }
             if self.commit_timestamp_unix {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env_map,
@@ -994,6 +1137,7 @@ impl AddEntries for Git2 {
             }
             if self.describe.is_some() {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitDescribe,
                     cargo_rustc_env_map,
@@ -1005,6 +1149,7 @@ impl AddEntries for Git2 {
                     add_map_entry(VergenKey::GitSha, sha.clone(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitSha,
                         cargo_rustc_env_map,
@@ -1017,6 +1162,7 @@ impl AddEntries for Git2 {
                     add_map_entry(VergenKey::GitDirty, dirty.to_string(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitDirty,
                         cargo_rustc_env_map,
@@ -1027,6 +1173,43 @@ impl AddEntries for Git2 {
             Ok(())
         }
     }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        // The inputs do not cover the state of the work tree, or a remote repository
+        let dirty = self.dirty.is_some() || self.describe.is_some_and(|describe| describe.dirty());
+        if dirty || self.remote_url.is_some() {
+            return None;
+        }
+        let mut inputs = vec![format!("{self:?}")];
+        inputs.extend(git_stamps(self.local_repo_path.as_deref())?);
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+
+    fn commit_time(&self) -> Result<Option<i64>, Error> {
+        let repo_dir = if let Some(path) = &self.local_repo_path {
+            path.clone()
+        } else {
+            env::current_dir().map_err(Error::io)?
+        };
+        let repo = Repository::discover(repo_dir).map_err(Error::not_a_git_repo)?;
+        let commit = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(Error::git_command_failed)?;
+        Ok(Some(commit.time().seconds()))
+    }
+
+    fn env_inputs(&self) -> Vec<&'static str> {
+        if self.commit_date || self.commit_timestamp {
+            vec![VERGEN_TZ]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1042,7 +1225,7 @@ mod test {
     use test_util::TEST_MTIME;
     use test_util::TestRepos;
     use vergen::Emitter;
-    use vergen_lib::{VergenKey, count_idempotent};
+    use vergen_lib::{EntryContext, Error, TimeFormat, VergenKey, count_idempotent};
 
     #[test]
     #[serial]
@@ -1077,9 +1260,11 @@ mod test {
     #[test]
     #[serial]
     fn empty_email_is_warning() -> Result<()> {
+        let context = &EntryContext::default();
         let mut cargo_rustc_env = BTreeMap::new();
         let mut cargo_warning = vec![];
         Git2::add_opt_value(
+            context,
             false,
             None,
             VergenKey::GitCommitAuthorEmail,
@@ -1094,9 +1279,11 @@ mod test {
     #[test]
     #[serial]
     fn empty_email_idempotent() -> Result<()> {
+        let context = &EntryContext::default();
         let mut cargo_rustc_env = BTreeMap::new();
         let mut cargo_warning = vec![];
         Git2::add_opt_value(
+            context,
             true,
             None,
             VergenKey::GitCommitAuthorEmail,
@@ -1111,10 +1298,18 @@ mod test {
     #[test]
     #[serial]
     fn bad_revwalk_is_warning() -> Result<()> {
+        let context = &EntryContext::default();
         let mut cargo_rustc_env = BTreeMap::new();
         let mut cargo_warning = vec![];
         let repo = Repository::discover(current_dir()?)?;
-        Git2::add_commit_count(false, true, &repo, &mut cargo_rustc_env, &mut cargo_warning);
+        Git2::add_commit_count(
+            context,
+            false,
+            true,
+            &repo,
+            &mut cargo_rustc_env,
+            &mut cargo_warning,
+        );
         assert_eq!(0, cargo_rustc_env.len());
         assert_eq!(1, cargo_warning.len());
         Ok(())
@@ -1123,10 +1318,18 @@ mod test {
     #[test]
     #[serial]
     fn bad_revwalk_idempotent() -> Result<()> {
+        let context = &EntryContext::default();
         let mut cargo_rustc_env = BTreeMap::new();
         let mut cargo_warning = vec![];
         let repo = Repository::discover(current_dir()?)?;
-        Git2::add_commit_count(true, true, &repo, &mut cargo_rustc_env, &mut cargo_warning);
+        Git2::add_commit_count(
+            context,
+            true,
+            true,
+            &repo,
+            &mut cargo_rustc_env,
+            &mut cargo_warning,
+        );
         assert_eq!(1, cargo_rustc_env.len());
         assert_eq!(1, cargo_warning.len());
         Ok(())
@@ -1135,6 +1338,7 @@ mod test {
     #[test]
     #[serial]
     fn head_not_found_is_default() -> Result<()> {
+        let context = &EntryContext::default();
         let test_repo = TestRepos::new(false, false, false)?;
         let mut map = BTreeMap::new();
         let mut cargo_warning = vec![];
@@ -1143,13 +1347,13 @@ mod test {
         // non-idempotent default, add_branch_name emits only a warning and no
         // map entry; on a normal branch checkout the branch name is added.
         let detached = repo.head_detached()?;
-        Git2::add_branch_name(false, true, &repo, &mut map, &mut cargo_warning)?;
+        Git2::add_branch_name(context, false, true, &repo, &mut map, &mut cargo_warning)?;
         assert_eq!(usize::from(!detached), map.len());
         assert_eq!(1, cargo_warning.len());
         let mut map = BTreeMap::new();
         let mut cargo_warning = vec![];
         let repo = Repository::discover(test_repo.path())?;
-        Git2::add_branch_name(false, true, &repo, &mut map, &mut cargo_warning)?;
+        Git2::add_branch_name(context, false, true, &repo, &mut map, &mut cargo_warning)?;
         assert_eq!(1, map.len());
         assert_eq!(1, cargo_warning.len());
         Ok(())
@@ -1214,12 +1418,11 @@ mod test {
     fn git_error_fails() -> Result<()> {
         let mut git2 = Git2::all_git();
         let _ = git2.fail();
-        assert!(
-            Emitter::default()
-                .fail_on_error()
-                .add_instructions(&git2)
-                .is_err()
-        );
+        let result = Emitter::default()
+            .fail_on_error()
+            .add_instructions(&git2)
+            .map(|_| ());
+        assert!(matches!(result, Err(Error::GitCommandFailed { .. })));
         Ok(())
     }
 
@@ -1284,7 +1487,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Git2::builder().commit_date(true).build();
                 Emitter::new()
@@ -1307,7 +1510,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Git2::builder().commit_date(true).build();
                 Emitter::new()
@@ -1332,7 +1535,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Git2::builder().commit_date(true).build();
                 Emitter::new()
@@ -1356,7 +1559,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Git2::builder().commit_date(true).build();
                 Emitter::new()
@@ -1524,6 +1727,37 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn commit_time_formats_work() -> Result<()> {
+        let git2 = Git2::builder()
+            .commit_date(true)
+            .commit_timestamp(true)
+            .commit_date_format(TimeFormat::Description("[year][month][day]".into()))
+            .commit_timestamp_format(TimeFormat::Compact)
+            .build();
+        let emitter = Emitter::default().add_instructions(&git2)?.test_emit();
+        let date = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitDate];
+        let timestamp = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestamp];
+        assert_eq!(8, date.len(), "date: {date}");
+        assert_eq!(12, timestamp.len(), "timestamp: {timestamp}");
+        assert!(timestamp.starts_with(date.as_str()));
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn commit_time_works() -> Result<()> {
+        use vergen_lib::AddEntries;
+
+        let git2 = Git2::builder().commit_timestamp_unix(true).build();
+        let emitter = Emitter::default().add_instructions(&git2)?.test_emit();
+        let unix = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestampUnix];
+        assert_eq!(Some(unix.parse::<i64>()?), git2.commit_time()?);
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn commit_timestamp_unix_works() -> Result<()> {
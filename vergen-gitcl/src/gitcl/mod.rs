@@ -7,7 +7,7 @@
 // modified, or distributed except according to those terms.
 
 use self::gitcl_builder::Empty;
-use anyhow::{Error, Result, anyhow};
+use anyhow::{Result, anyhow};
 use bon::Builder;
 use std::{
     env,
@@ -16,21 +16,11 @@ use std::{
 };
 #[cfg(feature = "allow_remote")]
 use std::{env::temp_dir, fs::create_dir_all};
-use time::{
-    OffsetDateTime, UtcOffset,
-    format_description::{
-        self,
-        well_known::{Iso8601, Rfc3339},
-    },
-};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 use vergen_lib::{
     AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, Describe,
-    Dirty, Sha, VergenKey, add_default_map_entry, add_map_entry,
-    constants::{
-        GIT_BRANCH_NAME, GIT_COMMIT_AUTHOR_EMAIL, GIT_COMMIT_AUTHOR_NAME, GIT_COMMIT_COUNT,
-        GIT_COMMIT_DATE_NAME, GIT_COMMIT_MESSAGE, GIT_COMMIT_TIMESTAMP_NAME,
-        GIT_COMMIT_TIMESTAMP_UNIX_NAME, GIT_DESCRIBE_NAME, GIT_DIRTY_NAME, GIT_SHA_NAME,
-    },
+    Dirty, EntryContext, Error, FromManifest, ManifestKeys, Sha, TimeFormat, TimeZone, VERGEN_TZ,
+    VergenKey, add_default_map_entry, add_map_entry, git_stamps, handle_key_failure, in_time_zone,
 };
 
 // This funkiness allows the command to be output in the docs
@@ -75,6 +65,12 @@ macro_rules! commit_timestamp {
     };
 }
 const COMMIT_TIMESTAMP: &str = commit_timestamp!();
+macro_rules! commit_time {
+    () => {
+        "git log -1 --format=%ct"
+    };
+}
+const COMMIT_TIME: &str = commit_time!();
 macro_rules! describe {
     () => {
         "git describe --always"
@@ -200,6 +196,7 @@ const DIRTY: &str = dirty!();
 /// cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 /// cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 /// cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+/// cargo:rerun-if-env-changed=VERGEN_TZ
 /// ```
 ///
 #[derive(Builder, Clone, Debug, PartialEq)]
@@ -367,6 +364,15 @@ pub struct Gitcl {
     /// Enable local offset date/timestamp output
     #[builder(default = false)]
     use_local: bool,
+    /// Render the date/timestamp output in the given time zone.  Takes precedence over
+    /// `use_local`, and is overridden by the `VERGEN_TZ` environment variable.
+    time_zone: Option<TimeZone>,
+    /// The format of the `VERGEN_GIT_COMMIT_DATE` output, `[year]-[month]-[day]` by default
+    #[builder(default = TimeFormat::Date)]
+    commit_date_format: TimeFormat,
+    /// The format of the `VERGEN_GIT_COMMIT_TIMESTAMP` output, ISO 8601 by default
+    #[builder(default = TimeFormat::Iso8601)]
+    commit_timestamp_format: TimeFormat,
     /// Specify the git cmd you wish to use, i.e. `/usr/bin/git`
     git_cmd: Option<&'static str>,
 }
@@ -431,7 +437,7 @@ impl Gitcl {
         if Self::git_cmd_exists(cmd) {
             Ok(())
         } else {
-            Err(anyhow!("no suitable 'git' command found!"))
+            Err(Error::git_command_failed("no suitable 'git' command found!").into())
         }
     }
 
@@ -439,7 +445,7 @@ impl Gitcl {
         if Self::inside_git_worktree(path) {
             Ok(())
         } else {
-            Err(anyhow!("not within a suitable 'git' worktree!"))
+            Err(Error::not_a_git_repo("not within a suitable 'git' worktree!").into())
         }
     }
 
@@ -523,13 +529,14 @@ impl Gitcl {
             Ok(output.stdout)
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(anyhow!("Failed to run '{command}'!  {stderr}"))
+            Err(Error::git_command_failed(format!("Failed to run '{command}'!  {stderr}")).into())
         }
     }
 
     #[allow(clippy::too_many_lines)]
     fn inner_add_git_map_entries(
         &self,
+        context: &EntryContext,
         repo_path: Option<&PathBuf>,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -541,8 +548,9 @@ impl Gitcl {
         }
 
         if self.branch {
-            if let Ok(_value) = env::var(GIT_BRANCH_NAME) {
+            if context.env_override(VergenKey::GitBranch).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitBranch,
                     cargo_rustc_env,
@@ -559,8 +567,12 @@ impl Gitcl {
         }
 
         if self.commit_author_email {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_EMAIL) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorEmail)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env,
@@ -577,8 +589,12 @@ impl Gitcl {
         }
 
         if self.commit_author_name {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_NAME) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorName)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env,
@@ -595,8 +611,9 @@ impl Gitcl {
         }
 
         if self.commit_count {
-            if let Ok(_value) = env::var(GIT_COMMIT_COUNT) {
+            if context.env_override(VergenKey::GitCommitCount).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitCount,
                     cargo_rustc_env,
@@ -613,6 +630,7 @@ impl Gitcl {
         }
 
         self.add_git_timestamp_entries(
+            context,
             COMMIT_TIMESTAMP,
             repo_path,
             idempotent,
@@ -621,8 +639,9 @@ impl Gitcl {
         )?;
 
         if self.commit_message {
-            if let Ok(_value) = env::var(GIT_COMMIT_MESSAGE) {
+            if context.env_override(VergenKey::GitCommitMessage).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env,
@@ -640,8 +659,9 @@ impl Gitcl {
 
         let mut dirty_cache = None; // attempt to re-use dirty status later if possible
         if let Some(dirty) = self.dirty {
-            if let Ok(_value) = env::var(GIT_DIRTY_NAME) {
+            if context.env_override(VergenKey::GitDirty).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDirty,
                     cargo_rustc_env,
@@ -665,8 +685,9 @@ impl Gitcl {
             // (see https://github.com/gitgitgadget/git/pull/1872)
             //
             // Instead, always compute the dirty status with `git status`
-            if let Ok(_value) = env::var(GIT_DESCRIBE_NAME) {
+            if context.env_override(VergenKey::GitDescribe).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDescribe,
                     cargo_rustc_env,
@@ -680,21 +701,33 @@ impl Gitcl {
                 if let Some(pattern) = *describe.match_pattern() {
                     Self::match_pattern_cmd_str(&mut describe_cmd, pattern);
                 }
-                let stdout = Self::run_cmd_checked(&describe_cmd, repo_path)?;
-                let mut describe_value = String::from_utf8_lossy(&stdout).trim().to_string();
-                if describe.dirty()
-                    && (dirty_cache.is_some_and(|dirty| dirty)
-                        || Self::compute_dirty(repo_path, false)?)
-                {
-                    describe_value.push_str("-dirty");
+                match Self::run_cmd_checked(&describe_cmd, repo_path) {
+                    Ok(stdout) => {
+                        let mut describe_value =
+                            String::from_utf8_lossy(&stdout).trim().to_string();
+                        if describe.dirty()
+                            && (dirty_cache.is_some_and(|dirty| dirty)
+                                || Self::compute_dirty(repo_path, false)?)
+                        {
+                            describe_value.push_str("-dirty");
+                        }
+                        add_map_entry(VergenKey::GitDescribe, describe_value, cargo_rustc_env);
+                    }
+                    Err(e) => handle_key_failure(
+                        context,
+                        VergenKey::GitDescribe,
+                        git_error(e),
+                        cargo_rustc_env,
+                        cargo_warning,
+                    )?,
                 }
-                add_map_entry(VergenKey::GitDescribe, describe_value, cargo_rustc_env);
             }
         }
 
         if let Some(sha) = self.sha {
-            if let Ok(_value) = env::var(GIT_SHA_NAME) {
+            if context.env_override(VergenKey::GitSha).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitSha,
                     cargo_rustc_env,
@@ -790,6 +823,7 @@ impl Gitcl {
     #[allow(clippy::too_many_lines)]
     fn add_git_timestamp_entries(
         &self,
+        context: &EntryContext,
         cmd: &str,
         path: Option<&PathBuf>,
         idempotent: bool,
@@ -797,8 +831,9 @@ impl Gitcl {
         cargo_warning: &mut CargoWarning,
     ) -> Result<()> {
         let mut date_override = false;
-        if let Ok(_value) = env::var(GIT_COMMIT_DATE_NAME) {
+        if context.env_override(VergenKey::GitCommitDate).is_some() {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitDate,
                 cargo_rustc_env,
@@ -808,8 +843,12 @@ impl Gitcl {
         }
 
         let mut timestamp_override = false;
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestamp)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestamp,
                 cargo_rustc_env,
@@ -819,8 +858,12 @@ impl Gitcl {
         }
 
         let mut timestamp_unix_override = false;
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_UNIX_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestampUnix)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestampUnix,
                 cargo_rustc_env,
@@ -847,6 +890,7 @@ impl Gitcl {
             if idempotent {
                 if self.commit_date && !date_override {
                     add_default_map_entry(
+                        context,
                         idempotent,
                         VergenKey::GitCommitDate,
                         cargo_rustc_env,
@@ -856,6 +900,7 @@ impl Gitcl {
 
                 if self.commit_timestamp && !timestamp_override {
                     add_default_map_entry(
+                        context,
                         idempotent,
                         VergenKey::GitCommitTimestamp,
                         cargo_rustc_env,
@@ -865,6 +910,7 @@ impl Gitcl {
 
                 if self.commit_timestamp_unix && !timestamp_unix_override {
                     add_default_map_entry(
+                        context,
                         idempotent,
                         VergenKey::GitCommitTimestampUnix,
                         cargo_rustc_env,
@@ -873,10 +919,9 @@ impl Gitcl {
                 }
             } else {
                 if self.commit_date && !date_override {
-                    let format = format_description::parse_borrowed::<1>("[year]-[month]-[day]")?;
                     add_map_entry(
                         VergenKey::GitCommitDate,
-                        ts.format(&format)?,
+                        self.commit_date_format.format(&ts)?,
                         cargo_rustc_env,
                     );
                 }
@@ -884,7 +929,7 @@ impl Gitcl {
                 if self.commit_timestamp && !timestamp_override {
                     add_map_entry(
                         VergenKey::GitCommitTimestamp,
-                        ts.format(&Iso8601::DEFAULT)?,
+                        self.commit_timestamp_format.format(&ts)?,
                         cargo_rustc_env,
                     );
                 }
@@ -900,6 +945,7 @@ impl Gitcl {
         } else {
             if self.commit_date && !date_override {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitDate,
                     cargo_rustc_env,
@@ -909,6 +955,7 @@ impl Gitcl {
 
             if self.commit_timestamp && !timestamp_override {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env,
@@ -918,6 +965,7 @@ impl Gitcl {
 
             if self.commit_timestamp_unix && !timestamp_unix_override {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env,
@@ -933,13 +981,11 @@ impl Gitcl {
     // this in not included in coverage, because on *nix the local offset is always unsafe
     fn compute_local_offset(&self, stdout: &str) -> Result<OffsetDateTime> {
         let no_offset = OffsetDateTime::parse(stdout, &Rfc3339)?;
-        if self.use_local {
-            let local = UtcOffset::local_offset_at(no_offset)?;
-            let local_offset = no_offset.checked_to_offset(local).unwrap_or(no_offset);
-            Ok(local_offset)
-        } else {
-            Ok(no_offset)
-        }
+        let zone = self
+            .time_zone
+            .as_ref()
+            .or(self.use_local.then_some(&TimeZone::Local));
+        Ok(in_time_zone(no_offset, zone)?)
     }
 
     fn compute_dirty(repo_path: Option<&PathBuf>, include_untracked: bool) -> Result<bool> {
@@ -1049,29 +1095,90 @@ impl Gitcl {
     }
 }
 
+/// Recover the [`Error`] raised while running git, or treat the failure as a failed git command
+fn git_error(error: anyhow::Error) -> Error {
+    Error::downcast_or(error.into(), Error::git_command_failed)
+}
+
+impl FromManifest for Gitcl {
+    const SECTION: &'static str = "git";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "branch",
+            "commit_author_name",
+            "commit_author_email",
+            "commit_count",
+            "commit_message",
+            "commit_date",
+            "commit_timestamp",
+            "commit_timestamp_unix",
+            "describe",
+            "describe_tags",
+            "describe_dirty",
+            "sha",
+            "sha_short",
+            "dirty",
+            "dirty_include_untracked",
+            "use_local",
+        ])?;
+        let mut gitcl = Self::builder()
+            .branch(keys.has("branch"))
+            .commit_author_name(keys.has("commit_author_name"))
+            .commit_author_email(keys.has("commit_author_email"))
+            .commit_count(keys.has("commit_count"))
+            .commit_message(keys.has("commit_message"))
+            .commit_date(keys.has("commit_date"))
+            .commit_timestamp(keys.has("commit_timestamp"))
+            .commit_timestamp_unix(keys.contains("commit_timestamp_unix"))
+            .use_local(keys.contains("use_local"))
+            .build();
+        gitcl.describe = keys.has("describe").then(|| {
+            Describe::builder()
+                .tags(keys.contains("describe_tags"))
+                .dirty(keys.contains("describe_dirty"))
+                .build()
+        });
+        gitcl.sha = keys
+            .has("sha")
+            .then(|| Sha::builder().short(keys.contains("sha_short")).build());
+        gitcl.dirty = keys.has("dirty").then(|| {
+            Dirty::builder()
+                .include_untracked(keys.contains("dirty_include_untracked"))
+                .build()
+        });
+        Ok(gitcl)
+    }
+}
+
 impl AddEntries for Gitcl {
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.any() {
             let git_cmd = self.git_cmd.unwrap_or("git --version");
-            Self::check_git(git_cmd)?;
+            Self::check_git(git_cmd).map_err(git_error)?;
 
-            let repo_path = self.setup_repo_path(self.local_repo_path.as_ref(), cargo_warning)?;
+            let repo_path = self
+                .setup_repo_path(self.local_repo_path.as_ref(), cargo_warning)
+                .map_err(git_error)?;
             let repo_path = repo_path.as_ref();
-            Self::check_inside_git_worktree(repo_path)?;
+            Self::check_inside_git_worktree(repo_path).map_err(git_error)?;
 
             self.inner_add_git_map_entries(
+                context,
                 repo_path,
                 idempotent,
                 cargo_rustc_env,
                 cargo_rerun_if_changed,
                 cargo_warning,
-            )?;
+            )
+            .map_err(git_error)?;
 
             self.cleanup();
         }
@@ -1082,13 +1189,13 @@ impl AddEntries for Gitcl {
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             // Clear any previous data.  We are re-populating
             // map isn't cleared because keys will overwrite.
@@ -1103,6 +1210,7 @@ impl AddEntries for Gitcl {
 
             if self.branch {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitBranch,
                     cargo_rustc_env_map,
@@ -1111,6 +1219,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_author_email {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env_map,
@@ -1119,6 +1228,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_author_name {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env_map,
@@ -1127,6 +1237,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_count {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitCount,
                     cargo_rustc_env_map,
@@ -1135,6 +1246,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_date {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitDate,
                     cargo_rustc_env_map,
@@ -1143,6 +1255,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_message {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env_map,
@@ -1151,6 +1264,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_timestamp {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env_map,
@@ -1159,6 +1273,7 @@ impl AddEntries for Gitcl {
             }
             if self.commit_timestamp_unix {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env_map,
@@ -1167,6 +1282,7 @@ impl AddEntries for Gitcl {
             }
             if self.describe.is_some() {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitDescribe,
                     cargo_rustc_env_map,
@@ -1178,6 +1294,7 @@ impl AddEntries for Gitcl {
                     add_map_entry(VergenKey::GitSha, sha.clone(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitSha,
                         cargo_rustc_env_map,
@@ -1190,6 +1307,7 @@ impl AddEntries for Gitcl {
                     add_map_entry(VergenKey::GitDirty, dirty.to_string(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitDirty,
                         cargo_rustc_env_map,
@@ -1200,6 +1318,39 @@ impl AddEntries for Gitcl {
             Ok(())
         }
     }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        // The inputs do not cover the state of the work tree, or a remote repository
+        let dirty = self.dirty.is_some() || self.describe.is_some_and(|describe| describe.dirty());
+        if dirty || self.remote_url.is_some() {
+            return None;
+        }
+        let mut inputs = vec![format!("{self:?}")];
+        inputs.extend(git_stamps(self.local_repo_path.as_deref())?);
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+
+    fn commit_time(&self) -> Result<Option<i64>, Error> {
+        let stdout =
+            Self::run_cmd_checked(COMMIT_TIME, self.local_repo_path.as_ref()).map_err(git_error)?;
+        let secs = String::from_utf8_lossy(&stdout)
+            .trim()
+            .parse()
+            .map_err(Error::git_command_failed)?;
+        Ok(Some(secs))
+    }
+
+    fn env_inputs(&self) -> Vec<&'static str> {
+        if self.commit_date || self.commit_timestamp {
+            vec![VERGEN_TZ]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1214,7 +1365,7 @@ mod test {
     #[cfg(unix)]
     use test_util::TEST_MTIME;
     use test_util::TestRepos;
-    use vergen_lib::{VergenKey, count_idempotent};
+    use vergen_lib::{EntryContext, Error, TimeFormat, VergenKey, count_idempotent};
 
     #[test]
     #[serial]
@@ -1265,7 +1416,8 @@ mod test {
     #[test]
     #[serial]
     fn non_working_tree_is_error() -> Result<()> {
-        assert!(Gitcl::check_inside_git_worktree(Some(&temp_dir())).is_err());
+        let result = Gitcl::check_inside_git_worktree(Some(&temp_dir())).map_err(super::git_error);
+        assert!(matches!(result, Err(Error::NotAGitRepo { .. })));
         Ok(())
     }
 
@@ -1376,12 +1528,11 @@ mod test {
     fn fails_on_bad_git_command() -> Result<()> {
         let mut gitcl = Gitcl::all_git();
         let _ = gitcl.git_cmd(Some("this_is_not_a_git_cmd"));
-        assert!(
-            Emitter::default()
-                .fail_on_error()
-                .add_instructions(&gitcl)
-                .is_err()
-        );
+        let result = Emitter::default()
+            .fail_on_error()
+            .add_instructions(&gitcl)
+            .map(|_| ());
+        assert!(matches!(result, Err(Error::GitCommandFailed { .. })));
         Ok(())
     }
 
@@ -1415,12 +1566,14 @@ mod test {
     #[test]
     #[serial]
     fn bad_timestamp_defaults() -> Result<()> {
+        let context = &EntryContext::default();
         let mut map = BTreeMap::new();
         let mut warnings = vec![];
         let gitcl = Gitcl::all_git();
         assert!(
             gitcl
                 .add_git_timestamp_entries(
+                    context,
                     "this_is_not_a_git_cmd",
                     None,
                     false,
@@ -1437,12 +1590,14 @@ mod test {
     #[test]
     #[serial]
     fn bad_timestamp_idempotent() -> Result<()> {
+        let context = &EntryContext::default();
         let mut map = BTreeMap::new();
         let mut warnings = vec![];
         let gitcl = Gitcl::all_git();
         assert!(
             gitcl
                 .add_git_timestamp_entries(
+                    context,
                     "this_is_not_a_git_cmd",
                     None,
                     true,
@@ -1490,7 +1645,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gitcl = Gitcl::builder().commit_date(true).build();
                 Emitter::new()
@@ -1513,7 +1668,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gitcl = Gitcl::builder().commit_date(true).build();
                 Emitter::new()
@@ -1538,7 +1693,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gitcl = Gitcl::builder().commit_date(true).build();
                 Emitter::new()
@@ -1562,7 +1717,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gitcl = Gitcl::builder().commit_date(true).build();
                 Emitter::new()
@@ -1729,6 +1884,37 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn commit_time_formats_work() -> Result<()> {
+        let gitcl = Gitcl::builder()
+            .commit_date(true)
+            .commit_timestamp(true)
+            .commit_date_format(TimeFormat::Description("[year][month][day]".into()))
+            .commit_timestamp_format(TimeFormat::Compact)
+            .build();
+        let emitter = Emitter::default().add_instructions(&gitcl)?.test_emit();
+        let date = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitDate];
+        let timestamp = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestamp];
+        assert_eq!(8, date.len(), "date: {date}");
+        assert_eq!(12, timestamp.len(), "timestamp: {timestamp}");
+        assert!(timestamp.starts_with(date.as_str()));
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn commit_time_works() -> Result<()> {
+        use vergen_lib::AddEntries;
+
+        let gitcl = Gitcl::builder().commit_timestamp_unix(true).build();
+        let emitter = Emitter::default().add_instructions(&gitcl)?.test_emit();
+        let unix = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestampUnix];
+        assert_eq!(Some(unix.parse::<i64>()?), gitcl.commit_time()?);
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn commit_timestamp_unix_works() -> Result<()> {
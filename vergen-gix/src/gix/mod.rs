@@ -7,7 +7,7 @@
 // modified, or distributed except according to those terms.
 
 use self::gix_builder::Empty;
-use anyhow::{Error, Result, anyhow};
+use anyhow::{Result, anyhow};
 use bon::Builder;
 use gix::{
     Commit, Head, Id, Repository,
@@ -19,18 +19,11 @@ use std::{
     env,
     path::{Path, PathBuf},
 };
-use time::{
-    OffsetDateTime, UtcOffset,
-    format_description::{self, well_known::Iso8601},
-};
+use time::OffsetDateTime;
 use vergen_lib::{
     AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, Describe,
-    Dirty, Sha, VergenKey, add_default_map_entry, add_map_entry,
-    constants::{
-        GIT_BRANCH_NAME, GIT_COMMIT_AUTHOR_EMAIL, GIT_COMMIT_AUTHOR_NAME, GIT_COMMIT_COUNT,
-        GIT_COMMIT_DATE_NAME, GIT_COMMIT_MESSAGE, GIT_COMMIT_TIMESTAMP_NAME,
-        GIT_COMMIT_TIMESTAMP_UNIX_NAME, GIT_DESCRIBE_NAME, GIT_DIRTY_NAME, GIT_SHA_NAME,
-    },
+    Dirty, EntryContext, Error, FromManifest, ManifestKeys, Sha, TimeFormat, TimeZone, VERGEN_TZ,
+    VergenKey, add_default_map_entry, add_map_entry, git_stamps, handle_key_failure, in_time_zone,
 };
 #[cfg(feature = "allow_remote")]
 use {
@@ -243,6 +236,15 @@ pub struct Gix {
     /// Enable local offset date/timestamp output
     #[builder(default = false)]
     use_local: bool,
+    /// Render the date/timestamp output in the given time zone.  Takes precedence over
+    /// `use_local`, and is overridden by the `VERGEN_TZ` environment variable.
+    time_zone: Option<TimeZone>,
+    /// The format of the `VERGEN_GIT_COMMIT_DATE` output, `[year]-[month]-[day]` by default
+    #[builder(default = TimeFormat::Date)]
+    commit_date_format: TimeFormat,
+    /// The format of the `VERGEN_GIT_COMMIT_TIMESTAMP` output, ISO 8601 by default
+    #[builder(default = TimeFormat::Iso8601)]
+    commit_timestamp_format: TimeFormat,
 }
 
 impl<S: gix_builder::State> GixBuilder<S> {
@@ -290,6 +292,7 @@ impl Gix {
 
     fn add_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
@@ -297,6 +300,7 @@ impl Gix {
     ) -> Result<()> {
         if self.any() {
             self.inner_add_git_map_entries(
+                context,
                 idempotent,
                 cargo_rustc_env,
                 cargo_rerun_if_changed,
@@ -334,7 +338,7 @@ impl Gix {
         repo_dir: &PathBuf,
         _warnings: &mut CargoWarning,
     ) -> Result<Repository> {
-        discover(repo_dir).map_err(Into::into)
+        discover(repo_dir).map_err(|e| Error::not_a_git_repo(e).into())
     }
 
     #[cfg(feature = "allow_remote")]
@@ -374,10 +378,11 @@ impl Gix {
             ));
             Ok(repo)
         } else {
-            Err(anyhow!(
+            Err(Error::not_a_git_repo(format!(
                 "Could not find a git repository at '{}'",
                 repo_dir.display()
             ))
+            .into())
         }
     }
 
@@ -403,6 +408,7 @@ impl Gix {
     #[allow(clippy::too_many_lines, clippy::default_trait_access)]
     fn inner_add_git_map_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
@@ -423,8 +429,9 @@ impl Gix {
         }
 
         if self.branch {
-            if let Ok(_value) = env::var(GIT_BRANCH_NAME) {
+            if context.env_override(VergenKey::GitBranch).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitBranch,
                     cargo_rustc_env,
@@ -439,8 +446,12 @@ impl Gix {
         }
 
         if self.commit_author_email {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_EMAIL) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorEmail)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env,
@@ -457,8 +468,12 @@ impl Gix {
         }
 
         if self.commit_author_name {
-            if let Ok(_value) = env::var(GIT_COMMIT_AUTHOR_NAME) {
+            if context
+                .env_override(VergenKey::GitCommitAuthorName)
+                .is_some()
+            {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env,
@@ -475,8 +490,9 @@ impl Gix {
         }
 
         if self.commit_count {
-            if let Ok(_value) = env::var(GIT_COMMIT_COUNT) {
+            if context.env_override(VergenKey::GitCommitCount).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitCount,
                     cargo_rustc_env,
@@ -491,11 +507,18 @@ impl Gix {
             }
         }
 
-        self.add_git_timestamp_entries(idempotent, &commit, cargo_rustc_env, cargo_warning)?;
+        self.add_git_timestamp_entries(
+            context,
+            idempotent,
+            &commit,
+            cargo_rustc_env,
+            cargo_warning,
+        )?;
 
         if self.commit_message {
-            if let Ok(_value) = env::var(GIT_COMMIT_MESSAGE) {
+            if context.env_override(VergenKey::GitCommitMessage).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env,
@@ -512,8 +535,9 @@ impl Gix {
         }
 
         if let Some(describe) = self.describe {
-            if let Ok(_value) = env::var(GIT_DESCRIBE_NAME) {
+            if context.env_override(VergenKey::GitDescribe).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDescribe,
                     cargo_rustc_env,
@@ -526,24 +550,38 @@ impl Gix {
                     SelectRef::AnnotatedTags
                 };
 
-                let describe = match commit.describe().names(describe_refs).try_resolve()? {
-                    Some(res) => {
-                        if describe.dirty() {
-                            let fmt = res.format_with_dirty_suffix(Some("dirty".to_string()))?;
-                            fmt.to_string()
-                        } else {
-                            res.format()?.to_string()
+                let describe_value = || -> Result<String> {
+                    let value = match commit.describe().names(describe_refs).try_resolve()? {
+                        Some(res) => {
+                            if describe.dirty() {
+                                let fmt =
+                                    res.format_with_dirty_suffix(Some("dirty".to_string()))?;
+                                fmt.to_string()
+                            } else {
+                                res.format()?.to_string()
+                            }
                         }
-                    }
-                    _ => String::new(),
+                        _ => String::new(),
+                    };
+                    Ok(value)
                 };
-                add_map_entry(VergenKey::GitDescribe, describe, cargo_rustc_env);
+                match describe_value() {
+                    Ok(value) => add_map_entry(VergenKey::GitDescribe, value, cargo_rustc_env),
+                    Err(e) => handle_key_failure(
+                        context,
+                        VergenKey::GitDescribe,
+                        Error::downcast_or(e.into(), Error::git_command_failed),
+                        cargo_rustc_env,
+                        cargo_warning,
+                    )?,
+                }
             }
         }
 
         if let Some(dirty) = self.dirty {
-            if let Ok(_value) = env::var(GIT_DIRTY_NAME) {
+            if context.env_override(VergenKey::GitDirty).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitDirty,
                     cargo_rustc_env,
@@ -570,8 +608,9 @@ impl Gix {
         }
 
         if let Some(sha) = self.sha {
-            if let Ok(_value) = env::var(GIT_SHA_NAME) {
+            if context.env_override(VergenKey::GitSha).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitSha,
                     cargo_rustc_env,
@@ -632,6 +671,7 @@ impl Gix {
 
     fn add_git_timestamp_entries(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         commit: &Commit<'_>,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -643,41 +683,57 @@ impl Gix {
         // timestamp (see issue #452).
         let ts = self.compute_local_offset(commit)?;
 
-        if let Ok(_value) = env::var(GIT_COMMIT_DATE_NAME) {
+        if context.env_override(VergenKey::GitCommitDate).is_some() {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitDate,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_date_entry(idempotent, &ts, cargo_rustc_env, cargo_warning)?;
+            self.add_git_date_entry(context, idempotent, &ts, cargo_rustc_env, cargo_warning)?;
         }
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestamp)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestamp,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_timestamp_entry(idempotent, &ts, cargo_rustc_env, cargo_warning)?;
+            self.add_git_timestamp_entry(context, idempotent, &ts, cargo_rustc_env, cargo_warning)?;
         }
-        if let Ok(_value) = env::var(GIT_COMMIT_TIMESTAMP_UNIX_NAME) {
+        if context
+            .env_override(VergenKey::GitCommitTimestampUnix)
+            .is_some()
+        {
             add_default_map_entry(
+                context,
                 idempotent,
                 VergenKey::GitCommitTimestampUnix,
                 cargo_rustc_env,
                 cargo_warning,
             );
         } else {
-            self.add_git_timestamp_unix_entry(idempotent, &ts, cargo_rustc_env, cargo_warning);
+            self.add_git_timestamp_unix_entry(
+                context,
+                idempotent,
+                &ts,
+                cargo_rustc_env,
+                cargo_warning,
+            );
         }
         Ok(())
     }
 
     fn add_git_timestamp_unix_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -686,6 +742,7 @@ impl Gix {
         if self.commit_timestamp_unix {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env,
@@ -705,17 +762,16 @@ impl Gix {
     // this in not included in coverage, because on *nix the local offset is always unsafe
     fn compute_local_offset(&self, commit: &Commit<'_>) -> Result<OffsetDateTime> {
         let no_offset = OffsetDateTime::from_unix_timestamp(commit.time()?.seconds)?;
-        if self.use_local {
-            let local = UtcOffset::local_offset_at(no_offset)?;
-            let local_offset = no_offset.checked_to_offset(local).unwrap_or(no_offset);
-            Ok(local_offset)
-        } else {
-            Ok(no_offset)
-        }
+        let zone = self
+            .time_zone
+            .as_ref()
+            .or(self.use_local.then_some(&TimeZone::Local));
+        Ok(in_time_zone(no_offset, zone)?)
     }
 
     fn add_git_date_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -724,16 +780,16 @@ impl Gix {
         if self.commit_date {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitDate,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                let format = format_description::parse_borrowed::<1>("[year]-[month]-[day]")?;
                 add_map_entry(
                     VergenKey::GitCommitDate,
-                    ts.format(&format)?,
+                    self.commit_date_format.format(ts)?,
                     cargo_rustc_env,
                 );
             }
@@ -743,6 +799,7 @@ impl Gix {
 
     fn add_git_timestamp_entry(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
@@ -751,6 +808,7 @@ impl Gix {
         if self.commit_timestamp {
             if idempotent {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env,
@@ -759,7 +817,7 @@ impl Gix {
             } else {
                 add_map_entry(
                     VergenKey::GitCommitTimestamp,
-                    ts.format(&Iso8601::DEFAULT)?,
+                    self.commit_timestamp_format.format(ts)?,
                     cargo_rustc_env,
                 );
             }
@@ -785,33 +843,87 @@ impl Gix {
     }
 }
 
+impl FromManifest for Gix {
+    const SECTION: &'static str = "git";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "branch",
+            "commit_author_name",
+            "commit_author_email",
+            "commit_count",
+            "commit_message",
+            "commit_date",
+            "commit_timestamp",
+            "commit_timestamp_unix",
+            "describe",
+            "describe_tags",
+            "describe_dirty",
+            "sha",
+            "sha_short",
+            "dirty",
+            "dirty_include_untracked",
+            "use_local",
+        ])?;
+        let mut gix = Self::builder()
+            .branch(keys.has("branch"))
+            .commit_author_name(keys.has("commit_author_name"))
+            .commit_author_email(keys.has("commit_author_email"))
+            .commit_count(keys.has("commit_count"))
+            .commit_message(keys.has("commit_message"))
+            .commit_date(keys.has("commit_date"))
+            .commit_timestamp(keys.has("commit_timestamp"))
+            .commit_timestamp_unix(keys.contains("commit_timestamp_unix"))
+            .use_local(keys.contains("use_local"))
+            .build();
+        gix.describe = keys.has("describe").then(|| {
+            Describe::builder()
+                .tags(keys.contains("describe_tags"))
+                .dirty(keys.contains("describe_dirty"))
+                .build()
+        });
+        gix.sha = keys
+            .has("sha")
+            .then(|| Sha::builder().short(keys.contains("sha_short")).build());
+        gix.dirty = keys.has("dirty").then(|| {
+            Dirty::builder()
+                .include_untracked(keys.contains("dirty_include_untracked"))
+                .build()
+        });
+        Ok(gix)
+    }
+}
+
 impl AddEntries for Gix {
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         self.add_entries(
+            context,
             idempotent,
             cargo_rustc_env,
             cargo_rerun_if_changed,
             cargo_warning,
         )
+        .map_err(|e| Error::downcast_or(e.into(), Error::git_command_failed))
     }
 
     #[allow(clippy::too_many_lines)]
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             // Clear any previous cargo_warning.  This should be it.
             cargo_warning.clear();
@@ -825,6 +937,7 @@ impl AddEntries for Gix {
 
             if self.branch {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitBranch,
                     cargo_rustc_env_map,
@@ -833,6 +946,7 @@ impl AddEntries for Gix {
             }
             if self.commit_author_email {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorEmail,
                     cargo_rustc_env_map,
@@ -841,6 +955,7 @@ impl AddEntries for Gix {
             }
             if self.commit_author_name {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitAuthorName,
                     cargo_rustc_env_map,
@@ -849,6 +964,7 @@ impl AddEntries for Gix {
             }
             if self.commit_count {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitCount,
                     cargo_rustc_env_map,
@@ -857,6 +973,7 @@ impl AddEntries for Gix {
             }
             if self.commit_date {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitDate,
                     cargo_rustc_env_map,
@@ -865,6 +982,7 @@ impl AddEntries for Gix {
             }
             if self.commit_message {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitMessage,
                     cargo_rustc_env_map,
@@ -873,6 +991,7 @@ impl AddEntries for Gix {
             }
             if self.commit_timestamp {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestamp,
                     cargo_rustc_env_map,
@@ -881,6 +1000,7 @@ impl AddEntries for Gix {
             }
             if self.commit_timestamp_unix {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitCommitTimestampUnix,
                     cargo_rustc_env_map,
@@ -889,6 +1009,7 @@ impl AddEntries for Gix {
             }
             if self.describe.is_some() {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::GitDescribe,
                     cargo_rustc_env_map,
@@ -900,6 +1021,7 @@ impl AddEntries for Gix {
                     add_map_entry(VergenKey::GitSha, sha.clone(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitSha,
                         cargo_rustc_env_map,
@@ -912,6 +1034,7 @@ impl AddEntries for Gix {
                     add_map_entry(VergenKey::GitDirty, dirty.to_string(), cargo_rustc_env_map);
                 } else {
                     add_default_map_entry(
+                        context,
                         *config.idempotent(),
                         VergenKey::GitDirty,
                         cargo_rustc_env_map,
@@ -922,6 +1045,41 @@ impl AddEntries for Gix {
             Ok(())
         }
     }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        // The inputs do not cover the state of the work tree, or a remote repository
+        let dirty = self.dirty.is_some() || self.describe.is_some_and(|describe| describe.dirty());
+        if dirty || self.remote_url.is_some() {
+            return None;
+        }
+        let mut inputs = vec![format!("{self:?}")];
+        inputs.extend(git_stamps(self.local_repo_path.as_deref())?);
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+
+    fn commit_time(&self) -> Result<Option<i64>, Error> {
+        let repo_dir = if let Some(path) = &self.local_repo_path {
+            path.clone()
+        } else {
+            env::current_dir().map_err(Error::io)?
+        };
+        let repo = discover(repo_dir).map_err(Error::not_a_git_repo)?;
+        let commit = repo.head_commit().map_err(Error::git_command_failed)?;
+        let time = commit.time().map_err(Error::git_command_failed)?;
+        Ok(Some(time.seconds))
+    }
+
+    fn env_inputs(&self) -> Vec<&'static str> {
+        if self.commit_date || self.commit_timestamp {
+            vec![VERGEN_TZ]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -936,7 +1094,7 @@ mod test {
     use test_util::TEST_MTIME;
     use test_util::TestRepos;
     use vergen::Emitter;
-    use vergen_lib::count_idempotent;
+    use vergen_lib::{Error, TimeFormat, VergenKey, count_idempotent};
 
     #[test]
     #[serial]
@@ -1154,12 +1312,11 @@ mod test {
     fn git_error_fails() -> Result<()> {
         let mut gix = Gix::all_git();
         let _ = gix.at_path(temp_dir());
-        assert!(
-            Emitter::default()
-                .fail_on_error()
-                .add_instructions(&gix)
-                .is_err()
-        );
+        let result = Emitter::default()
+            .fail_on_error()
+            .add_instructions(&gix)
+            .map(|_| ());
+        assert!(matches!(result, Err(Error::NotAGitRepo { .. })));
         Ok(())
     }
 
@@ -1224,7 +1381,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<()> {
+            let result = || -> Result<(), Error> {
                 let gix = Gix::builder().commit_date(true).build();
                 Emitter::new()
                     .idempotent()
@@ -1246,7 +1403,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Gix::builder().commit_date(true).build();
                 Emitter::new()
@@ -1271,7 +1428,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Gix::builder().commit_date(true).build();
                 Emitter::default()
@@ -1295,7 +1452,7 @@ mod test {
         let os_string = OsString::from_wide(&source[..]);
         let os_str = os_string.as_os_str();
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let gix = Gix::builder().commit_date(true).build();
                 Emitter::default()
@@ -1442,6 +1599,37 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn commit_time_formats_work() -> Result<()> {
+        let gix = Gix::builder()
+            .commit_date(true)
+            .commit_timestamp(true)
+            .commit_date_format(TimeFormat::Description("[year][month][day]".into()))
+            .commit_timestamp_format(TimeFormat::Compact)
+            .build();
+        let emitter = Emitter::default().add_instructions(&gix)?.test_emit();
+        let date = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitDate];
+        let timestamp = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestamp];
+        assert_eq!(8, date.len(), "date: {date}");
+        assert_eq!(12, timestamp.len(), "timestamp: {timestamp}");
+        assert!(timestamp.starts_with(date.as_str()));
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn commit_time_works() -> Result<()> {
+        use vergen_lib::AddEntries;
+
+        let gix = Gix::builder().commit_timestamp_unix(true).build();
+        let emitter = Emitter::default().add_instructions(&gix)?.test_emit();
+        let unix = &emitter.cargo_rustc_env_map()[&VergenKey::GitCommitTimestampUnix];
+        assert_eq!(Some(unix.parse::<i64>()?), gix.commit_time()?);
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn commit_timestamp_unix_works() -> Result<()> {
@@ -28,6 +28,10 @@
 //!   to rerun instruction emission if the `VERGEN_DEFAULT_ON_ERROR` environment variable has changed.
 //! - Will emit [`cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed)
 //!   to rerun instruction emission if the `SOURCE_DATE_EPOCH` environment variable has changed.
+//! - Will emit [`cargo:rerun-if-env-changed=VERGEN_TZ`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed)
+//!   to rerun instruction emission if the `VERGEN_TZ` environment variable has changed, when date or timestamp output is enabled.
+//! - Will emit [`cargo:rerun-if-env-changed=<VARIABLE>`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed)
+//!   for each override environment variable consulted, i.e. `VERGEN_GIT_SHA`, to rerun instruction emission if an override has changed.
 //!
 //! ## Usage
 //!
@@ -47,9 +51,9 @@
 //!
 //! [build-dependencies]
 //! # All features enabled
-//! vergen-gix = { version = "10", features = ["build", "cargo", "rustc", "si"] }
+//! vergen-gix = { version = "11", features = ["build", "cargo", "rustc", "si"] }
 //! # or
-//! vergen-gix = { version = "10", features = ["build"] }
+//! vergen-gix = { version = "11", features = ["build"] }
 //! # if you wish to disable certain features
 //! ```
 //!
@@ -135,6 +139,39 @@
 //! cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 //! cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 //! cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+//! cargo:rerun-if-env-changed=VERGEN_TZ
+//! cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+//! cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_DEBUG
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_FEATURES
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_OPT_LEVEL
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_TARGET_TRIPLE
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_DEPENDENCIES
+//! cargo:rerun-if-env-changed=VERGEN_GIT_BRANCH
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_AUTHOR_EMAIL
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_AUTHOR_NAME
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_COUNT
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_DATE
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_MESSAGE
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_TIMESTAMP
+//! cargo:rerun-if-env-changed=VERGEN_GIT_DESCRIBE
+//! cargo:rerun-if-env-changed=VERGEN_GIT_DIRTY
+//! cargo:rerun-if-env-changed=VERGEN_GIT_SHA
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_CHANNEL
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_COMMIT_DATE
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_COMMIT_HASH
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_HOST_TRIPLE
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_LLVM_VERSION
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_SEMVER
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_NAME
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_OS_VERSION
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_USER
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_TOTAL_MEMORY
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_VENDOR
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_CORE_COUNT
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_NAME
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_BRAND
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_FREQUENCY
 //! ```
 //!
 //! #### Generate specific output
@@ -200,6 +237,35 @@ let build = Build::builder().build_timestamp(true).build();"
 //! cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 //! cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 //! cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+//! cargo:rerun-if-env-changed=VERGEN_TZ
+//! cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
+//! cargo:rerun-if-env-changed=VERGEN_CARGO_OPT_LEVEL
+//! cargo:rerun-if-env-changed=VERGEN_GIT_COMMIT_TIMESTAMP
+//! cargo:rerun-if-env-changed=VERGEN_RUSTC_SEMVER
+//! cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_CORE_COUNT
+//! ```
+//!
+//! #### Declarative configuration
+//! The output can also be configured without writing the build script, in the
+//! `[package.metadata.vergen]` table of `Cargo.toml` or in a `vergen.toml` file next to it.
+//! See [`Emitter::from_manifest`] for the options and instruction sets.
+//!
+//! ```toml
+//! [package.metadata.vergen]
+//! build = ["timestamp"]
+//! cargo = ["opt_level"]
+//! git = ["sha", "describe"]
+//! rustc = ["semver"]
+//! sysinfo = ["cpu_core_count"]
+//! ```
+//!
+//! ```
+//! # use anyhow::Result;
+//! #
+//! # pub fn main() -> Result<()> {
+//! vergen_gix::from_manifest()?.emit()?;
+//! #   Ok(())
+//! # }
 //! ```
 //!
 //! 4. Use the [`env!`](std::env!) or [`option_env!`](std::option_env!) macro in your code to read the environment variables.
@@ -214,15 +280,20 @@ let build = Build::builder().build_timestamp(true).build();"
 //! ```
 //!
 //! ## Features
-//! `vergen-gix` has four main feature toggles allowing you to customize your output. No features are enabled by default.
+//! `vergen-gix` has five main feature toggles allowing you to customize your output. No features are enabled by default.
 //! You **must** specifically enable the features you wish to use.
 //!
 //! | Feature | Enables |
 //! | ------- | ------- |
 //! |  build  | `VERGEN_BUILD_*` instructions |
 //! |  cargo  | `VERGEN_CARGO_*` instructions |
+//! |   ci    | `VERGEN_CI_*` instructions |
 //! |  rustc  | `VERGEN_RUSTC_*` instructions |
 //! |   si    | `VERGEN_SYSINFO_*` instructions |
+#![cfg_attr(
+    feature = "redact",
+    doc = r"| redact  | Redaction of emitted values, see [`redact`](Emitter::redact) |"
+)]
 //!
 //! ## Environment Variables
 //! `vergen-gix` currently recognizes the following environment variables.  The full list of the environment variable names can be
@@ -234,6 +305,7 @@ let build = Build::builder().build_timestamp(true).build();"
 //! | `SOURCE_DATE_EPOCH` | If this environment variable is set `vergen` will use the value (unix time since epoch) as the basis for a time based instructions.  This can help emit deterministic instructions. |
 //! | `VERGEN_BUILD_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
 //! | `VERGEN_CARGO_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
+//! | `VERGEN_CI_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
 //! | `VERGEN_GIT_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
 //! | `VERGEN_RUSTC_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
 //! | `VERGEN_SYSINFO_*` | If this environment variable is set `vergen` will use the value you specify for the output rather than generating it. |
@@ -470,6 +542,7 @@ use regex as _;
 #[cfg(not(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "rustc",
     feature = "si"
 )))]
@@ -482,6 +555,8 @@ pub use self::gix::Gix;
 pub use vergen::Build;
 #[cfg(feature = "cargo")]
 pub use vergen::Cargo;
+#[cfg(feature = "ci")]
+pub use vergen::Ci;
 #[cfg(feature = "si")]
 pub use vergen::CpuRefreshKind;
 #[cfg(feature = "cargo_metadata")]
@@ -499,5 +574,63 @@ pub use vergen::Sysinfo;
 pub use vergen_lib::AddCustomEntries;
 pub use vergen_lib::CargoRerunIfChanged;
 pub use vergen_lib::CargoWarning;
+pub use vergen_lib::CfgKind;
+pub use vergen_lib::CollisionPolicy;
+pub use vergen_lib::CustomValue;
 pub use vergen_lib::DefaultConfig;
 pub use vergen_lib::Emitter;
+pub use vergen_lib::Error;
+pub use vergen_lib::FailurePolicy;
+pub use vergen_lib::FromManifest;
+#[cfg(feature = "build")]
+pub use vergen_lib::Granularity;
+pub use vergen_lib::InstructionSyntax;
+pub use vergen_lib::KeyStatus;
+pub use vergen_lib::ManifestFormat;
+pub use vergen_lib::ManifestKeys;
+#[cfg(feature = "redact")]
+pub use vergen_lib::Redaction;
+pub use vergen_lib::Report;
+pub use vergen_lib::ReportEntry;
+pub use vergen_lib::TimeFormat;
+pub use vergen_lib::TimeZone;
+pub use vergen_lib::Transform;
+pub use vergen_lib::ValueEncoding;
+pub use vergen_lib::VergenKey;
+
+/// Instantiate an [`Emitter`] configured by the `[package.metadata.vergen]` table of
+/// `Cargo.toml`, or by `vergen.toml`, and add each of the configured instruction sets
+///
+/// See [`Emitter::from_manifest`] for the configuration.
+///
+/// # Errors
+///
+/// An [`Error::InvalidConfig`] if the configuration is invalid.  Otherwise, as for
+/// [`Emitter::add_instructions`].
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// #
+/// # fn main() -> Result<()> {
+/// vergen_gix::from_manifest()?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+pub fn from_manifest() -> Result<Emitter, Error> {
+    let mut emitter = Emitter::from_manifest()?;
+    #[cfg(feature = "build")]
+    let _ = emitter.add_manifest_instructions::<Build>()?;
+    #[cfg(feature = "cargo")]
+    let _ = emitter.add_manifest_instructions::<Cargo>()?;
+    #[cfg(feature = "ci")]
+    let _ = emitter.add_manifest_instructions::<Ci>()?;
+    let _ = emitter.add_manifest_instructions::<Gix>()?;
+    #[cfg(feature = "rustc")]
+    let _ = emitter.add_manifest_instructions::<Rustc>()?;
+    #[cfg(feature = "si")]
+    let _ = emitter.add_manifest_instructions::<Sysinfo>()?;
+    Ok(emitter)
+}
@@ -0,0 +1,235 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Memoization of instruction sets between build script runs
+
+use crate::{
+    CargoRustcEnvMap, VergenKey,
+    encoding::{escape, unescape},
+};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Write as _,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    process,
+    time::UNIX_EPOCH,
+};
+
+const HEADER: &str = "vergen-cache 1";
+
+/// The output of an instruction set as stored in the cache
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Cached {
+    pub(crate) entries: CargoRustcEnvMap,
+    pub(crate) rerun_if_changed: Vec<String>,
+    /// The override environment variables consulted, and their values when cached
+    pub(crate) overrides: BTreeMap<VergenKey, Option<String>>,
+}
+
+/// The cache file for the given instruction set identity
+pub(crate) fn cache_file(dir: &Path, identity: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    identity.hash(&mut hasher);
+    dir.join(format!("vergen-cache-{:016x}", hasher.finish()))
+}
+
+/// Load the cached output if it was stored with the same key and none of the override
+/// environment variables it consulted have changed
+pub(crate) fn load<F>(file: &Path, key: &str, env_name: F) -> Option<Cached>
+where
+    F: Fn(VergenKey) -> String,
+{
+    let contents = fs::read_to_string(file).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != HEADER || unescape(lines.next()?.strip_prefix("key ")?)? != key {
+        return None;
+    }
+    let mut cached = Cached::default();
+    for line in lines {
+        let (kind, rest) = line.split_once(' ')?;
+        match kind {
+            "env" => {
+                let (name, value) = rest.split_once(' ')?;
+                let _old = cached.entries.insert(key_named(name)?, unescape(value)?);
+            }
+            "rerun" => cached.rerun_if_changed.push(unescape(rest)?),
+            "override" => {
+                let (name, value) = rest.split_once(' ')?;
+                let _old = cached
+                    .overrides
+                    .insert(key_named(name)?, Some(unescape(value)?));
+            }
+            "unset" => {
+                let _old = cached.overrides.insert(key_named(rest)?, None);
+            }
+            _ => return None,
+        }
+    }
+    cached
+        .overrides
+        .iter()
+        .all(|(key, value)| env::var(env_name(*key)).ok() == *value)
+        .then_some(cached)
+}
+
+/// Store the output, ignoring any failure as the cache is only an optimization
+pub(crate) fn store(file: &Path, key: &str, cached: &Cached) {
+    let mut contents = format!("{HEADER}\nkey {}\n", escape(key));
+    for (k, v) in &cached.entries {
+        _ = writeln!(contents, "env {} {}", k.name(), escape(v));
+    }
+    for path in &cached.rerun_if_changed {
+        _ = writeln!(contents, "rerun {}", escape(path));
+    }
+    for (k, v) in &cached.overrides {
+        _ = match v {
+            Some(v) => writeln!(contents, "override {} {}", k.name(), escape(v)),
+            None => writeln!(contents, "unset {}", k.name()),
+        };
+    }
+    // Write and rename, so a concurrent load never sees a partially written file
+    let mut tmp = file.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", process::id()));
+    let _res = fs::create_dir_all(file.parent().unwrap_or(file))
+        .and_then(|()| fs::write(&tmp, contents))
+        .and_then(|()| fs::rename(&tmp, file));
+}
+
+fn key_named(name: &str) -> Option<VergenKey> {
+    VergenKey::all()
+        .iter()
+        .copied()
+        .find(|key| key.name() == name)
+}
+
+/// A cache input for the given path that changes when the file is modified,
+/// based on its length and modification time
+///
+/// See [`AddEntries::cache_inputs`](crate::AddEntries::cache_inputs).
+#[must_use]
+pub fn file_stamp(path: &Path) -> String {
+    match fs::metadata(path) {
+        Ok(metadata) => {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_nanos());
+            format!("{}:{}:{modified}", path.display(), metadata.len())
+        }
+        Err(_) => format!("{}:missing", path.display()),
+    }
+}
+
+/// A cache input for the given path that changes when the file contents change,
+/// i.e. for a `Cargo.lock` that is rewritten with the same contents
+///
+/// See [`AddEntries::cache_inputs`](crate::AddEntries::cache_inputs).
+#[must_use]
+pub fn file_digest(path: &Path) -> String {
+    match fs::read(path) {
+        Ok(contents) => {
+            let mut hasher = DefaultHasher::new();
+            contents.hash(&mut hasher);
+            format!("{}:{:016x}", path.display(), hasher.finish())
+        }
+        Err(_) => format!("{}:missing", path.display()),
+    }
+}
+
+/// The cache inputs for the git repository containing the given path, or the current
+/// directory: the checked out `HEAD`, the commit it refers to, and the tags
+///
+/// These do not cover the state of the work tree, so instruction sets that report
+/// whether it is dirty should not be cached.  Returns `None` if the repository cannot
+/// be located without running git, i.e. when `GIT_DIR` is set.
+///
+/// See [`AddEntries::cache_inputs`](crate::AddEntries::cache_inputs).
+#[cfg(feature = "git")]
+#[must_use]
+pub fn git_stamps(path: Option<&Path>) -> Option<Vec<String>> {
+    if env::var_os("GIT_DIR").is_some() {
+        return None;
+    }
+    let start = match path {
+        Some(path) => path.to_path_buf(),
+        None => env::current_dir().ok()?,
+    };
+    let git_dir = find_git_dir(&start)?;
+    let common_dir = fs::read_to_string(git_dir.join("commondir"))
+        .map_or_else(|_| git_dir.clone(), |common| git_dir.join(common.trim()));
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let mut stamps = vec![format!("HEAD={head}")];
+    if let Some(reference) = head.strip_prefix("ref: ") {
+        stamps.push(file_digest(&common_dir.join(reference)));
+    }
+    stamps.push(file_stamp(&common_dir.join("packed-refs")));
+    stamps.push(file_stamp(&common_dir.join("refs").join("tags")));
+    stamps.push(file_stamp(&common_dir.join("shallow")));
+    Some(stamps)
+}
+
+/// The git directory of the repository containing the given directory
+pub(crate) fn find_git_dir(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if let Ok(contents) = fs::read_to_string(&dot_git) {
+            return contents
+                .trim()
+                .strip_prefix("gitdir: ")
+                .map(|git_dir| dir.join(git_dir));
+        }
+    }
+    None
+}
+
+#[cfg(all(test, feature = "cargo"))]
+mod test {
+    use super::{Cached, cache_file, file_digest, file_stamp, load, store};
+    use crate::VergenKey;
+    use serial_test::serial;
+    use std::{env, fs};
+
+    #[test]
+    #[serial]
+    fn store_load_works() {
+        let dir = env::temp_dir().join("vergen_cache_store_load");
+        let file = cache_file(&dir, "cargo");
+        let mut cached = Cached::default();
+        let _old = cached
+            .entries
+            .insert(VergenKey::CargoDebug, "multi\nline".to_string());
+        cached.rerun_if_changed.push("/a path/HEAD".to_string());
+        let _old = cached.overrides.insert(VergenKey::CargoDebug, None);
+        store(&file, "key\none", &cached);
+        let name = |key: VergenKey| key.name().to_string();
+        assert_eq!(Some(&cached), load(&file, "key\none", name).as_ref());
+        assert!(load(&file, "key\ntwo", name).is_none());
+        temp_env::with_var("VERGEN_CARGO_DEBUG", Some("overridden"), || {
+            assert!(load(&file, "key\none", name).is_none());
+        });
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stamps_work() {
+        let missing = env::temp_dir().join("vergen_cache_missing");
+        assert!(file_stamp(&missing).ends_with(":missing"));
+        assert!(file_digest(&missing).ends_with(":missing"));
+        let manifest = env::current_dir().unwrap().join("Cargo.toml");
+        assert!(!file_stamp(&manifest).ends_with(":missing"));
+        assert_eq!(file_digest(&manifest), file_digest(&manifest));
+    }
+}
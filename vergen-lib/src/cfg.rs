@@ -0,0 +1,159 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `cargo:rustc-cfg` generation from emitted values
+
+use crate::constants::VERGEN_IDEMPOTENT_DEFAULT;
+use std::io::{Result, Write};
+
+/// How a [`VergenKey`](crate::VergenKey) is turned into a `rustc` cfg
+///
+/// See [`Emitter::rustc_cfg`](crate::Emitter::rustc_cfg).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CfgKind {
+    /// A name-only cfg, i.e. `#[cfg(vergen_git_dirty)]`, set when the value is `true`
+    Bool,
+    /// A key-value cfg, i.e. `#[cfg(vergen_rustc_channel = "nightly")]`
+    KeyValue,
+}
+
+/// The cfg name for the given variable name, i.e. `VERGEN_GIT_DIRTY` becomes `vergen_git_dirty`
+pub(crate) fn cfg_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| *c != '\n')
+        .fold(String::new(), |mut acc, c| {
+            if c == '"' || c == '\\' {
+                acc.push('\\');
+            }
+            acc.push(c);
+            acc
+        })
+}
+
+/// Write the `cargo:rustc-check-cfg` declaration and, if the value warrants it,
+/// the `cargo:rustc-cfg` instruction for a single variable.
+///
+/// Values that are missing or set to the idempotent default never set a cfg.
+pub(crate) fn write_cfg<T>(
+    out: &mut T,
+    prefix: &str,
+    name: &str,
+    kind: CfgKind,
+    value: Option<&str>,
+) -> Result<()>
+where
+    T: Write,
+{
+    let cfg = cfg_name(name);
+    let value = value.filter(|v| *v != VERGEN_IDEMPOTENT_DEFAULT);
+    match kind {
+        CfgKind::Bool => {
+            writeln!(out, "{prefix}rustc-check-cfg=cfg({cfg})")?;
+            if value.is_some_and(|v| v.trim() == "true") {
+                writeln!(out, "{prefix}rustc-cfg={cfg}")?;
+            }
+        }
+        CfgKind::KeyValue => {
+            writeln!(out, "{prefix}rustc-check-cfg=cfg({cfg}, values(any()))")?;
+            if let Some(value) = value {
+                writeln!(out, "{prefix}rustc-cfg={cfg}=\"{}\"", escape(value))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CfgKind, cfg_name, escape, write_cfg};
+    use anyhow::Result;
+
+    #[test]
+    fn cfg_name_works() {
+        assert_eq!("vergen_git_dirty", cfg_name("VERGEN_GIT_DIRTY"));
+        assert_eq!("vergen_cl", cfg_name("vergen-cl"));
+    }
+
+    #[test]
+    fn escape_works() {
+        assert_eq!(r#"a \"b\" \\c"#, escape("a \"b\" \\c\n"));
+    }
+
+    #[test]
+    fn bool_cfg_works() -> Result<()> {
+        let mut buf = vec![];
+        write_cfg(
+            &mut buf,
+            "cargo:",
+            "VERGEN_GIT_DIRTY",
+            CfgKind::Bool,
+            Some("true"),
+        )?;
+        write_cfg(
+            &mut buf,
+            "cargo:",
+            "VERGEN_CARGO_DEBUG",
+            CfgKind::Bool,
+            Some("false"),
+        )?;
+        assert_eq!(
+            "cargo:rustc-check-cfg=cfg(vergen_git_dirty)\n\
+             cargo:rustc-cfg=vergen_git_dirty\n\
+             cargo:rustc-check-cfg=cfg(vergen_cargo_debug)\n",
+            String::from_utf8_lossy(&buf)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn key_value_cfg_works() -> Result<()> {
+        let mut buf = vec![];
+        write_cfg(
+            &mut buf,
+            "cargo:",
+            "VERGEN_RUSTC_CHANNEL",
+            CfgKind::KeyValue,
+            Some("nightly"),
+        )?;
+        write_cfg(
+            &mut buf,
+            "cargo:",
+            "VERGEN_GIT_SHA",
+            CfgKind::KeyValue,
+            None,
+        )?;
+        write_cfg(
+            &mut buf,
+            "cargo:",
+            "VERGEN_GIT_BRANCH",
+            CfgKind::KeyValue,
+            Some("VERGEN_IDEMPOTENT_OUTPUT"),
+        )?;
+        assert_eq!(
+            "cargo:rustc-check-cfg=cfg(vergen_rustc_channel, values(any()))\n\
+             cargo:rustc-cfg=vergen_rustc_channel=\"nightly\"\n\
+             cargo:rustc-check-cfg=cfg(vergen_git_sha, values(any()))\n\
+             cargo:rustc-check-cfg=cfg(vergen_git_branch, values(any()))\n",
+            String::from_utf8_lossy(&buf)
+        );
+        Ok(())
+    }
+}
@@ -6,7 +6,10 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+#[cfg(any(feature = "build", feature = "git"))]
+use crate::Error;
 use bon::Builder;
+use std::sync::Arc;
 
 // Common configuration structs
 
@@ -74,3 +77,159 @@ impl Dirty {
         self.include_untracked
     }
 }
+
+/// The format of a date or timestamp output, i.e. `VERGEN_BUILD_DATE` or
+/// `VERGEN_GIT_COMMIT_TIMESTAMP`
+///
+/// | Format | Sample |
+/// | ------ | ------ |
+/// | [`Date`](TimeFormat::Date) | 2022-12-23 |
+/// | [`Iso8601`](TimeFormat::Iso8601) | 2022-12-23T15:29:20.000000000Z |
+/// | [`Rfc2822`](TimeFormat::Rfc2822) | Fri, 23 Dec 2022 15:29:20 +0000 |
+/// | [`Rfc3339`](TimeFormat::Rfc3339) | 2022-12-23T15:29:20Z |
+/// | [`Compact`](TimeFormat::Compact) | 202212231529 |
+/// | [`Description`](TimeFormat::Description) | i.e. `[year].[month].[day]` gives 2022.12.23 |
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TimeFormat {
+    /// `[year]-[month]-[day]`, the default for dates
+    Date,
+    /// ISO 8601 with nanosecond precision, the default for timestamps
+    Iso8601,
+    /// RFC 2822
+    Rfc2822,
+    /// RFC 3339 with seconds precision
+    Rfc3339,
+    /// The compact `YYYYMMDDHHMM`
+    Compact,
+    /// A [`time` format description](https://time-rs.github.io/book/api/format-description.html)
+    Description(Arc<str>),
+}
+
+#[cfg(any(feature = "build", feature = "git"))]
+impl TimeFormat {
+    /// Format the given date and time
+    ///
+    /// # Errors
+    ///
+    /// * The format description is invalid.
+    /// * The date and time cannot be represented in the format, i.e. a year before 1900
+    ///   in RFC 2822.
+    ///
+    pub fn format(&self, ts: &time::OffsetDateTime) -> Result<String, Error> {
+        use time::format_description::well_known::{Iso8601, Rfc2822, Rfc3339};
+
+        match self {
+            TimeFormat::Date => Self::describe("[year]-[month]-[day]", ts),
+            TimeFormat::Iso8601 => ts.format(&Iso8601::DEFAULT).map_err(Error::time_format),
+            TimeFormat::Rfc2822 => ts.format(&Rfc2822).map_err(Error::time_format),
+            TimeFormat::Rfc3339 => ts
+                .replace_nanosecond(0)
+                .map_err(Error::time_format)?
+                .format(&Rfc3339)
+                .map_err(Error::time_format),
+            TimeFormat::Compact => Self::describe("[year][month][day][hour][minute]", ts),
+            TimeFormat::Description(description) => Self::describe(description, ts),
+        }
+    }
+
+    fn describe(description: &str, ts: &time::OffsetDateTime) -> Result<String, Error> {
+        let format = time::format_description::parse_borrowed::<1>(description)
+            .map_err(Error::time_format)?;
+        ts.format(&format).map_err(Error::time_format)
+    }
+}
+
+/// The granularity the build date and timestamp are rounded down to
+///
+/// Rounding keeps the `VERGEN_BUILD_*` output stable between builds within the same
+/// minute, hour or day, so that caches such as `sccache` are not invalidated by every
+/// run of the build script.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Granularity {
+    /// Round down to the start of the minute
+    Minute,
+    /// Round down to the start of the hour
+    Hour,
+    /// Round down to midnight
+    Day,
+}
+
+#[cfg(feature = "build")]
+impl Granularity {
+    /// Round the given date and time down to the start of the minute, hour or day, in
+    /// its offset
+    ///
+    /// # Errors
+    ///
+    /// * The rounded time cannot be represented.
+    ///
+    pub fn round_down(self, ts: time::OffsetDateTime) -> Result<time::OffsetDateTime, Error> {
+        let (hour, minute) = match self {
+            Granularity::Minute => (ts.hour(), ts.minute()),
+            Granularity::Hour => (ts.hour(), 0),
+            Granularity::Day => (0, 0),
+        };
+        let time = time::Time::from_hms(hour, minute, 0).map_err(Error::time_format)?;
+        Ok(ts.replace_time(time))
+    }
+}
+
+#[cfg(all(test, any(feature = "build", feature = "git")))]
+mod test {
+    #[cfg(feature = "build")]
+    use super::Granularity;
+    use super::TimeFormat;
+    use anyhow::Result;
+    use time::OffsetDateTime;
+    #[cfg(feature = "build")]
+    use time::format_description::well_known::Rfc3339;
+
+    #[test]
+    fn time_format_works() -> Result<()> {
+        let ts = OffsetDateTime::from_unix_timestamp_nanos(1_671_809_360_123_456_789)?;
+        assert_eq!("2022-12-23", TimeFormat::Date.format(&ts)?);
+        assert_eq!(
+            "2022-12-23T15:29:20.123456789Z",
+            TimeFormat::Iso8601.format(&ts)?
+        );
+        assert_eq!(
+            "Fri, 23 Dec 2022 15:29:20 +0000",
+            TimeFormat::Rfc2822.format(&ts)?
+        );
+        assert_eq!("2022-12-23T15:29:20Z", TimeFormat::Rfc3339.format(&ts)?);
+        assert_eq!("202212231529", TimeFormat::Compact.format(&ts)?);
+        assert_eq!(
+            "2022.12.23",
+            TimeFormat::Description("[year].[month].[day]".into()).format(&ts)?
+        );
+        assert!(
+            TimeFormat::Description("[bogus]".into())
+                .format(&ts)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "build")]
+    fn round_down_works() -> Result<()> {
+        let ts = OffsetDateTime::from_unix_timestamp_nanos(1_671_809_360_123_456_789)?;
+        let rounded = |granularity: Granularity| -> Result<String> {
+            Ok(TimeFormat::Iso8601.format(&granularity.round_down(ts)?)?)
+        };
+        assert_eq!(
+            "2022-12-23T15:29:00.000000000Z",
+            rounded(Granularity::Minute)?
+        );
+        assert_eq!(
+            "2022-12-23T15:00:00.000000000Z",
+            rounded(Granularity::Hour)?
+        );
+        assert_eq!("2022-12-23T00:00:00.000000000Z", rounded(Granularity::Day)?);
+
+        let offset = time::UtcOffset::from_hms(5, 30, 0)?;
+        let local = Granularity::Day.round_down(ts.to_offset(offset))?;
+        assert_eq!("2022-12-23T00:00:00+05:30", local.format(&Rfc3339)?);
+        Ok(())
+    }
+}
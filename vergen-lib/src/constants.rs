@@ -10,10 +10,17 @@
 
 /// The default idempotent output string
 pub const VERGEN_IDEMPOTENT_DEFAULT: &str = "VERGEN_IDEMPOTENT_OUTPUT";
+/// The marker prefixed to a value encoded with [`ValueEncoding::Escaped`](crate::ValueEncoding::Escaped)
+pub const VERGEN_ESCAPED_MARKER: &str = "vergen-escaped:";
+/// The marker prefixed to a value encoded with [`ValueEncoding::Base64`](crate::ValueEncoding::Base64)
+pub const VERGEN_BASE64_MARKER: &str = "vergen-base64:";
+/// The default link section written by [`Emitter::link_section`](crate::Emitter::link_section)
+pub const VERGEN_LINK_SECTION: &str = ".vergen_info";
 
 #[cfg(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "git",
     feature = "rustc",
     feature = "si"
@@ -24,6 +31,7 @@ pub use self::features::*;
 #[cfg(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "git",
     feature = "rustc",
     feature = "si"
@@ -108,6 +116,25 @@ pub mod features {
     #[cfg(feature = "cargo")]
     pub const CARGO_TARGET_TRIPLE: &str = "VERGEN_CARGO_TARGET_TRIPLE";
 
+    /// The CI provider running the build
+    #[cfg(feature = "ci")]
+    pub const CI_PROVIDER: &str = "VERGEN_CI_PROVIDER";
+    /// The identifier of the CI pipeline or workflow run
+    #[cfg(feature = "ci")]
+    pub const CI_RUN_ID: &str = "VERGEN_CI_RUN_ID";
+    /// The URL of the CI pipeline or workflow run
+    #[cfg(feature = "ci")]
+    pub const CI_RUN_URL: &str = "VERGEN_CI_RUN_URL";
+    /// The name of the CI job
+    #[cfg(feature = "ci")]
+    pub const CI_JOB_NAME: &str = "VERGEN_CI_JOB_NAME";
+    /// The event that triggered the CI run
+    #[cfg(feature = "ci")]
+    pub const CI_EVENT: &str = "VERGEN_CI_EVENT";
+    /// The number of the pull request being built
+    #[cfg(feature = "ci")]
+    pub const CI_PR_NUMBER: &str = "VERGEN_CI_PR_NUMBER";
+
     /// The system name
     #[cfg(feature = "si")]
     pub const SYSINFO_NAME: &str = "VERGEN_SYSINFO_NAME";
@@ -142,6 +169,7 @@ pub mod features {
 #[cfg(not(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "git",
     feature = "rustc",
     feature = "si"
@@ -153,6 +181,7 @@ pub mod features {}
     any(
         feature = "build",
         feature = "cargo",
+        feature = "ci",
         feature = "git",
         feature = "rustc",
         feature = "si"
@@ -178,6 +207,18 @@ mod test {
         assert_eq!(CARGO_FEATURES, "VERGEN_CARGO_FEATURES");
     }
 
+    #[cfg(feature = "ci")]
+    #[test]
+    fn ci_constants_dont_change() {
+        // ci Constants
+        assert_eq!(CI_PROVIDER, "VERGEN_CI_PROVIDER");
+        assert_eq!(CI_RUN_ID, "VERGEN_CI_RUN_ID");
+        assert_eq!(CI_RUN_URL, "VERGEN_CI_RUN_URL");
+        assert_eq!(CI_JOB_NAME, "VERGEN_CI_JOB_NAME");
+        assert_eq!(CI_EVENT, "VERGEN_CI_EVENT");
+        assert_eq!(CI_PR_NUMBER, "VERGEN_CI_PR_NUMBER");
+    }
+
     #[cfg(feature = "git")]
     #[test]
     fn git_constants_dont_change() {
@@ -0,0 +1,182 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Typed values of custom instructions
+
+use crate::source::ValueKind;
+use std::borrow::Cow;
+
+/// The typed value of a custom instruction
+///
+/// The type determines how the value is emitted, the type of the `pub const` generated
+/// for it by [`Emitter::rust_source`](crate::Emitter::rust_source), and whether it is
+/// replaced by the idempotent default when the emitter is
+/// [`idempotent`](crate::Emitter::idempotent), exactly as for the built-in keys.  Strings
+/// convert into [`CustomValue::Str`], so existing [`AddCustomEntries`](crate::AddCustomEntries)
+/// implementations are unchanged.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CustomValue {
+    /// A string, emitted as is
+    Str(String),
+    /// A flag, emitted as `true` or `false`, like `VERGEN_GIT_DIRTY`
+    Bool(bool),
+    /// An integer, like `VERGEN_GIT_COMMIT_COUNT`
+    Int(i64),
+    /// A point in time as Unix seconds, emitted in the format of `VERGEN_BUILD_TIMESTAMP`,
+    /// i.e. `2022-12-23T15:29:20.000000000Z`.  Like the build timestamp it is emitted as
+    /// the idempotent default when the emitter is idempotent.
+    Timestamp(i64),
+    /// A list, emitted comma separated, like `VERGEN_CARGO_FEATURES`
+    List(Vec<String>),
+}
+
+impl CustomValue {
+    /// Render the value as it is emitted
+    pub(crate) fn render(&self) -> String {
+        match self {
+            CustomValue::Str(value) => value.clone(),
+            CustomValue::Bool(value) => value.to_string(),
+            CustomValue::Int(value) => value.to_string(),
+            CustomValue::Timestamp(secs) => timestamp(*secs),
+            CustomValue::List(items) => items.join(","),
+        }
+    }
+
+    /// The Rust type the value is generated as
+    pub(crate) fn kind(&self) -> ValueKind {
+        match self {
+            CustomValue::Str(_) | CustomValue::Timestamp(_) => ValueKind::Str,
+            CustomValue::Bool(_) => ValueKind::Bool,
+            CustomValue::Int(_) => ValueKind::I64,
+            CustomValue::List(_) => ValueKind::List,
+        }
+    }
+
+    /// Whether the value changes from build to build, and so is replaced by the
+    /// idempotent default when the emitter is idempotent
+    pub(crate) fn is_volatile(&self) -> bool {
+        matches!(self, CustomValue::Timestamp(_))
+    }
+}
+
+/// Format Unix seconds as an ISO 8601 UTC timestamp with nanosecond precision
+fn timestamp(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let time = secs.rem_euclid(86_400);
+    // Civil from days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.000000000Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+impl From<String> for CustomValue {
+    fn from(value: String) -> Self {
+        CustomValue::Str(value)
+    }
+}
+
+impl From<&String> for CustomValue {
+    fn from(value: &String) -> Self {
+        CustomValue::Str(value.clone())
+    }
+}
+
+impl From<&str> for CustomValue {
+    fn from(value: &str) -> Self {
+        CustomValue::Str(value.to_string())
+    }
+}
+
+impl From<Cow<'_, str>> for CustomValue {
+    fn from(value: Cow<'_, str>) -> Self {
+        CustomValue::Str(value.into_owned())
+    }
+}
+
+impl From<bool> for CustomValue {
+    fn from(value: bool) -> Self {
+        CustomValue::Bool(value)
+    }
+}
+
+impl From<i64> for CustomValue {
+    fn from(value: i64) -> Self {
+        CustomValue::Int(value)
+    }
+}
+
+impl From<i32> for CustomValue {
+    fn from(value: i32) -> Self {
+        CustomValue::Int(value.into())
+    }
+}
+
+impl From<u32> for CustomValue {
+    fn from(value: u32) -> Self {
+        CustomValue::Int(value.into())
+    }
+}
+
+impl From<Vec<String>> for CustomValue {
+    fn from(value: Vec<String>) -> Self {
+        CustomValue::List(value)
+    }
+}
+
+impl From<Vec<&str>> for CustomValue {
+    fn from(value: Vec<&str>) -> Self {
+        CustomValue::List(value.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl From<&[&str]> for CustomValue {
+    fn from(value: &[&str]) -> Self {
+        CustomValue::List(value.iter().map(|item| (*item).to_string()).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CustomValue;
+
+    #[test]
+    fn render_works() {
+        assert_eq!("value", CustomValue::from("value").render());
+        assert_eq!("true", CustomValue::from(true).render());
+        assert_eq!("-42", CustomValue::from(-42).render());
+        assert_eq!("a,b", CustomValue::from(vec!["a", "b"]).render());
+        assert_eq!(
+            "1970-01-01T00:00:00.000000000Z",
+            CustomValue::Timestamp(0).render()
+        );
+        assert_eq!(
+            "2022-12-23T15:29:20.000000000Z",
+            CustomValue::Timestamp(1_671_809_360).render()
+        );
+        assert_eq!(
+            "2000-02-29T23:59:59.000000000Z",
+            CustomValue::Timestamp(951_868_799).render()
+        );
+        assert_eq!(
+            "1969-12-31T23:59:59.000000000Z",
+            CustomValue::Timestamp(-1).render()
+        );
+    }
+}
@@ -0,0 +1,614 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Declarative configuration from `[package.metadata.vergen]` or `vergen.toml`
+
+#[cfg(any(feature = "build", feature = "git"))]
+use crate::TimeZone;
+use crate::{AddEntries, Error, Granularity, TimeFormat};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Debug, Formatter},
+    fs,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+    sync::Arc,
+};
+
+const TABLE: &str = "package.metadata.vergen";
+
+/// The configuration options of the emitter
+const OPTIONS: &[&str] = &[
+    "cache",
+    "concurrent",
+    "default_on_error",
+    "fail_on_error",
+    "idempotent",
+    "key_prefix",
+    "link_section",
+    "quiet",
+    "rust_source",
+    "workspace_shared",
+];
+
+/// The instruction set sections
+const SECTIONS: &[&str] = &["build", "cargo", "ci", "git", "rustc", "sysinfo"];
+
+/// Instruction sets that can be configured declaratively, with a list of the keys
+/// to emit, and of `key=value` options
+///
+/// ```toml
+/// [package.metadata.vergen]
+/// build = ["timestamp", "timestamp_format=rfc3339", "round_down=hour"]
+/// git = ["sha", "describe"]
+/// ```
+///
+/// See [`Emitter::from_manifest`](crate::Emitter::from_manifest).
+pub trait FromManifest: Sized {
+    /// The name of the section, i.e. `git`
+    const SECTION: &'static str;
+
+    /// Configure the instruction set to emit the requested keys
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::InvalidConfig`] if an unknown key is requested.
+    ///
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error>;
+}
+
+/// The keys requested for an instruction set in the declarative configuration
+///
+/// A section set to `true`, or a list containing `"all"`, requests all of the keys
+/// the instruction set enables by default, i.e. as `Build::all_build` does.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManifestKeys {
+    section: &'static str,
+    all: bool,
+    keys: Vec<String>,
+    git: Option<Shared>,
+}
+
+/// An instruction set added from the declarative configuration, shared with the
+/// instruction sets configured after it
+#[derive(Clone)]
+struct Shared(Arc<dyn AddEntries + Send + Sync>);
+
+impl Debug for Shared {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Shared")
+            .field(&self.0.workspace_identity())
+            .finish()
+    }
+}
+
+impl PartialEq for Shared {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Shared {}
+
+impl ManifestKeys {
+    /// Whether the key is requested, either explicitly or through `all`
+    #[must_use]
+    pub fn has(&self, key: &str) -> bool {
+        self.all || self.contains(key)
+    }
+
+    /// Whether the key is requested explicitly, for opt-in keys and options that are
+    /// not enabled through `all`
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+
+    /// The value of the `key=value` option, i.e. `Europe/Berlin` for
+    /// `"time_zone=Europe/Berlin"`, if it is set
+    #[must_use]
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.keys
+            .iter()
+            .find_map(|k| k.split_once('=').filter(|(name, _)| *name == key))
+            .map(|(_, value)| value)
+    }
+
+    /// The [`TimeFormat`] set by the option, one of `date`, `iso8601`, `rfc2822`,
+    /// `rfc3339` or `compact`, or otherwise a `time` format description
+    #[must_use]
+    pub fn time_format(&self, key: &str) -> Option<TimeFormat> {
+        self.value(key).map(|value| match value {
+            "date" => TimeFormat::Date,
+            "iso8601" => TimeFormat::Iso8601,
+            "rfc2822" => TimeFormat::Rfc2822,
+            "rfc3339" => TimeFormat::Rfc3339,
+            "compact" => TimeFormat::Compact,
+            description => TimeFormat::Description(description.into()),
+        })
+    }
+
+    /// The [`TimeZone`] set by the option, `UTC`, `local`, a fixed offset or an IANA
+    /// time zone name, as for the `VERGEN_TZ` environment variable
+    #[cfg(any(feature = "build", feature = "git"))]
+    #[must_use]
+    pub fn time_zone(&self, key: &str) -> Option<TimeZone> {
+        self.value(key).map(TimeZone::parse)
+    }
+
+    /// The [`Granularity`] set by the option, one of `minute`, `hour` or `day`
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::InvalidConfig`] if the value is not a granularity.
+    ///
+    pub fn granularity(&self, key: &str) -> Result<Option<Granularity>, Error> {
+        match self.value(key) {
+            None => Ok(None),
+            Some("minute") => Ok(Some(Granularity::Minute)),
+            Some("hour") => Ok(Some(Granularity::Hour)),
+            Some("day") => Ok(Some(Granularity::Day)),
+            Some(value) => Err(self.invalid_value(key, value, "minute, hour, day")),
+        }
+    }
+
+    /// The instruction set configured by the `git` section, if it was added before this
+    /// one, i.e. to read the commit time from
+    #[must_use]
+    pub fn git(&self) -> Option<Arc<dyn AddEntries + Send + Sync>> {
+        self.git.as_ref().map(|git| Arc::clone(&git.0))
+    }
+
+    /// An [`Error::InvalidConfig`] for an option set to an unknown value
+    #[must_use]
+    pub fn invalid_value(&self, key: &str, value: &str, expected: &str) -> Error {
+        Error::invalid_config(format!(
+            "invalid {} value '{key}={value}', expected one of: {expected}",
+            self.section
+        ))
+    }
+
+    /// Check that only the given keys are requested
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::InvalidConfig`] naming the first unknown key.
+    ///
+    pub fn check(&self, known: &[&str]) -> Result<(), Error> {
+        match self.keys.iter().find(|key| {
+            let name = key.split_once('=').map_or(key.as_str(), |(name, _)| name);
+            name != "all" && !known.contains(&name)
+        }) {
+            Some(key) => Err(Error::invalid_config(format!(
+                "unknown {} key '{key}', expected one of: all, {}",
+                self.section,
+                known.join(", ")
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A value in the declarative configuration
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Value {
+    Bool(bool),
+    String(String),
+    List(Vec<String>),
+}
+
+/// The declarative configuration loaded by [`Emitter::from_manifest`](crate::Emitter::from_manifest)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Declarative {
+    path: PathBuf,
+    values: BTreeMap<String, Value>,
+    added: BTreeSet<&'static str>,
+    git: Option<Shared>,
+}
+
+impl Declarative {
+    /// Load `vergen.toml` from the given directory, or if it does not exist, the
+    /// `[package.metadata.vergen]` table of `Cargo.toml`
+    pub(crate) fn load(dir: &Path) -> Result<Self, Error> {
+        let vergen_toml = dir.join("vergen.toml");
+        let (path, table) = if vergen_toml.is_file() {
+            (vergen_toml, None)
+        } else {
+            (dir.join("Cargo.toml"), Some(TABLE))
+        };
+        let contents = fs::read_to_string(&path)?;
+        let values = parse(&contents, table)
+            .map_err(|e| Error::invalid_config(format!("{}: {e}", path.display())))?;
+        if let Some(key) = values
+            .keys()
+            .find(|key| !OPTIONS.contains(&key.as_str()) && !SECTIONS.contains(&key.as_str()))
+        {
+            return Err(Error::invalid_config(format!(
+                "{}: unknown key '{key}'",
+                path.display()
+            )));
+        }
+        Ok(Self {
+            path,
+            values,
+            added: BTreeSet::new(),
+            git: None,
+        })
+    }
+
+    /// The configuration file
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn bool(&self, key: &str) -> Result<bool, Error> {
+        match self.values.get(key) {
+            None => Ok(false),
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(_) => Err(self.invalid(key, "a boolean")),
+        }
+    }
+
+    pub(crate) fn string(&self, key: &str) -> Result<Option<&str>, Error> {
+        match self.values.get(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(self.invalid(key, "a string")),
+        }
+    }
+
+    /// The keys requested for the given section, if the instruction set is enabled,
+    /// marking the section as added
+    pub(crate) fn keys(&mut self, section: &'static str) -> Result<Option<ManifestKeys>, Error> {
+        let _new = self.added.insert(section);
+        let (all, keys) = match self.values.get(section) {
+            None | Some(Value::Bool(false)) => return Ok(None),
+            Some(Value::Bool(true)) => (true, vec![]),
+            Some(Value::List(keys)) => (keys.iter().any(|key| key == "all"), keys.clone()),
+            Some(Value::String(_)) => return Err(self.invalid(section, "a boolean or a list")),
+        };
+        Ok(Some(ManifestKeys {
+            section,
+            all,
+            keys,
+            git: self.git.clone(),
+        }))
+    }
+
+    /// Share the instruction set added for the `git` section with the sections added
+    /// after it
+    pub(crate) fn set_git(&mut self, git: Arc<dyn AddEntries + Send + Sync>) {
+        self.git = Some(Shared(git));
+    }
+
+    /// The configured sections no instruction set was added for
+    pub(crate) fn missing_sections(&self) -> impl Iterator<Item = &str> {
+        SECTIONS.iter().copied().filter(|section| {
+            !self.added.contains(section)
+                && !matches!(self.values.get(*section), None | Some(Value::Bool(false)))
+        })
+    }
+
+    fn invalid(&self, key: &str, expected: &str) -> Error {
+        Error::invalid_config(format!(
+            "{}: '{key}' should be {expected}",
+            self.path.display()
+        ))
+    }
+}
+
+/// Parse the top level keys of the TOML document, or the keys of the given table
+///
+/// Only booleans, strings and lists of strings are supported, which is all the
+/// declarative configuration uses.
+fn parse(contents: &str, table: Option<&str>) -> Result<BTreeMap<String, Value>, String> {
+    let mut values = BTreeMap::new();
+    let mut in_table = table.is_none();
+    let mut lines = contents.lines().map(strip_comment);
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            let header = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .replace([' ', '"'], "");
+            in_table = !line.starts_with("[[") && Some(header.as_str()) == table;
+            continue;
+        }
+        if !in_table {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected 'key = value', found '{line}'"))?;
+        let key = key.trim().trim_matches('"').to_string();
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !balanced(&value) {
+            let next = lines
+                .next()
+                .ok_or_else(|| format!("unterminated list for '{key}'"))?;
+            value.push(' ');
+            value.push_str(next.trim());
+        }
+        let mut chars = value.chars().peekable();
+        let parsed = parse_value(&mut chars).map_err(|e| format!("'{key}': {e}"))?;
+        if chars.any(|c| !c.is_whitespace()) {
+            return Err(format!("'{key}': unexpected characters after the value"));
+        }
+        if values.insert(key.clone(), parsed).is_some() {
+            return Err(format!("duplicate key '{key}'"));
+        }
+    }
+    Ok(values)
+}
+
+/// Remove a comment, outside of any string, from the line
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..idx],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+/// Whether the brackets of a list, outside of any string, are balanced
+fn balanced(value: &str) -> bool {
+    let mut depth = 0_i32;
+    let mut quote = None;
+    let mut escaped = false;
+    for c in value.chars() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+        escaped = false;
+    }
+    depth <= 0
+}
+
+fn parse_value(chars: &mut Peekable<Chars<'_>>) -> Result<Value, String> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('"' | '\'') => parse_string(chars).map(Value::String),
+        Some('[') => {
+            let _bracket = chars.next();
+            let mut list = vec![];
+            loop {
+                skip_whitespace(chars);
+                if chars.next_if_eq(&']').is_some() {
+                    break;
+                }
+                list.push(parse_string(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => break,
+                    _ => return Err("expected ',' or ']' in list".to_string()),
+                }
+            }
+            Ok(Value::List(list))
+        }
+        _ => {
+            let word: String = chars.by_ref().take_while(|c| !c.is_whitespace()).collect();
+            match word.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(format!(
+                    "unsupported value '{word}', expected a boolean, a string or a list of strings"
+                )),
+            }
+        }
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars<'_>>) -> Result<String, String> {
+    let quote = chars
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')
+        .ok_or("expected a string")?;
+    let mut string = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == quote => return Ok(string),
+            '\\' if quote == '"' => match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some(c @ ('"' | '\\')) => string.push(c),
+                _ => return Err("unsupported escape in string".to_string()),
+            },
+            c => string.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ManifestKeys, Value, parse};
+    #[cfg(any(feature = "build", feature = "git"))]
+    use crate::TimeZone;
+    use crate::{Error, Granularity, TimeFormat};
+
+    const CARGO_TOML: &str = r#"
+[package]
+name = "test"
+keywords = [
+    "[not a table]",
+]
+
+[package.metadata.vergen]
+idempotent = true # comment
+key_prefix = "MY#APP"
+build = ["timestamp"]
+git = [
+    "sha",   # the sha
+    'describe',
+]
+sysinfo = false
+
+[dependencies]
+anyhow = "1"
+"#;
+
+    #[test]
+    fn parse_table_works() {
+        let values = parse(CARGO_TOML, Some("package.metadata.vergen")).unwrap();
+        assert_eq!(5, values.len());
+        assert_eq!(Some(&Value::Bool(true)), values.get("idempotent"));
+        assert_eq!(
+            Some(&Value::String("MY#APP".to_string())),
+            values.get("key_prefix")
+        );
+        assert_eq!(
+            Some(&Value::List(vec![
+                "sha".to_string(),
+                "describe".to_string()
+            ])),
+            values.get("git")
+        );
+        assert_eq!(Some(&Value::Bool(false)), values.get("sysinfo"));
+    }
+
+    #[test]
+    fn parse_top_level_works() {
+        let values = parse("build = true\n\n[other]\ncargo = true\n", None).unwrap();
+        assert_eq!(1, values.len());
+        assert_eq!(Some(&Value::Bool(true)), values.get("build"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("build = 1", None).is_err());
+        assert!(parse("build = [\"a\"", None).is_err());
+        assert!(parse("build = [\"a\" \"b\"]", None).is_err());
+        assert!(parse("build = \"a", None).is_err());
+        assert!(parse("build = true true", None).is_err());
+        assert!(parse("build = true\nbuild = false", None).is_err());
+        assert!(parse("build", None).is_err());
+    }
+
+    #[test]
+    fn manifest_keys_works() {
+        let keys = ManifestKeys {
+            section: "build",
+            all: false,
+            keys: vec!["timestamp".to_string(), "bogus".to_string()],
+            git: None,
+        };
+        assert!(keys.has("timestamp"));
+        assert!(!keys.has("date"));
+        assert!(matches!(
+            keys.check(&["date", "timestamp"]),
+            Err(Error::InvalidConfig { .. })
+        ));
+        let keys = ManifestKeys {
+            section: "build",
+            all: true,
+            keys: vec![],
+            git: None,
+        };
+        assert!(keys.has("date"));
+        assert!(!keys.contains("date"));
+        assert!(keys.check(&["date"]).is_ok());
+    }
+
+    fn options(options: &[&str]) -> ManifestKeys {
+        ManifestKeys {
+            section: "build",
+            all: false,
+            keys: options.iter().map(ToString::to_string).collect(),
+            git: None,
+        }
+    }
+
+    #[test]
+    fn manifest_options_work() {
+        let keys = options(&["timestamp", "round_down=hour", "time_zone=Europe/Berlin"]);
+        assert!(
+            keys.check(&["timestamp", "round_down", "time_zone"])
+                .is_ok()
+        );
+        assert!(matches!(
+            keys.check(&["timestamp", "round_down"]),
+            Err(Error::InvalidConfig { .. })
+        ));
+        assert_eq!(Some("hour"), keys.value("round_down"));
+        assert_eq!(Some("Europe/Berlin"), keys.value("time_zone"));
+        assert_eq!(None, keys.value("timestamp"));
+        assert!(!keys.contains("round_down"));
+        assert!(keys.git().is_none());
+    }
+
+    #[test]
+    fn time_format_option_works() {
+        let keys = options(&["date_format=rfc2822", "timestamp_format=[year].[month]"]);
+        assert_eq!(Some(TimeFormat::Rfc2822), keys.time_format("date_format"));
+        assert_eq!(
+            Some(TimeFormat::Description("[year].[month]".into())),
+            keys.time_format("timestamp_format")
+        );
+        assert_eq!(None, keys.time_format("format"));
+    }
+
+    #[test]
+    #[cfg(any(feature = "build", feature = "git"))]
+    fn time_zone_option_works() {
+        let zone = |spec: &str| options(&[&format!("time_zone={spec}")]).time_zone("time_zone");
+        assert_eq!(Some(TimeZone::Utc), zone("UTC"));
+        assert_eq!(Some(TimeZone::Local), zone("local"));
+        assert_eq!(
+            Some(TimeZone::Offset(
+                time::UtcOffset::from_hms(5, 30, 0).unwrap()
+            )),
+            zone("+05:30")
+        );
+        assert_eq!(
+            Some(TimeZone::Named("Europe/Berlin".into())),
+            zone("Europe/Berlin")
+        );
+    }
+
+    #[test]
+    fn granularity_option_works() {
+        let keys = options(&["round_down=day"]);
+        assert_eq!(
+            Some(Granularity::Day),
+            keys.granularity("round_down").unwrap()
+        );
+        assert_eq!(None, keys.granularity("other").unwrap());
+        assert!(matches!(
+            options(&["round_down=week"]).granularity("round_down"),
+            Err(Error::InvalidConfig { .. })
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Encoding of values that cannot be passed through a `cargo:rustc-env` instruction verbatim
+
+use crate::constants::{VERGEN_BASE64_MARKER, VERGEN_ESCAPED_MARKER};
+use std::fmt::Write as _;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How values containing newlines and other ASCII control characters are emitted
+///
+/// A `cargo:rustc-env` instruction ends at the first newline, so by default newlines
+/// are removed from the emitted values.  The lossless encodings prefix the encoded
+/// value with a marker, i.e. [`VERGEN_ESCAPED_MARKER`], so it can be decoded at
+/// runtime with `vergen_pretty::decode` or at compile time with `vergen_pretty::vergen_decode!`.
+/// Values without control characters are always emitted verbatim.
+///
+/// See [`Emitter::value_encoding`](crate::Emitter::value_encoding).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ValueEncoding {
+    /// Remove newlines, passing all other characters through
+    #[default]
+    Strip,
+    /// Backslash-escape the control characters, i.e. a newline becomes `\n`, keeping
+    /// the value readable
+    Escaped,
+    /// Encode the whole value as standard, padded base64
+    Base64,
+}
+
+impl ValueEncoding {
+    /// Encode a value for a `cargo:rustc-env` instruction
+    pub(crate) fn encode(self, value: &str) -> String {
+        match self {
+            ValueEncoding::Strip => value.chars().filter(|c| *c != '\n').collect(),
+            _ if !needs_encoding(value) => value.to_string(),
+            ValueEncoding::Escaped => format!("{VERGEN_ESCAPED_MARKER}{}", escape(value)),
+            ValueEncoding::Base64 => format!("{VERGEN_BASE64_MARKER}{}", base64(value.as_bytes())),
+        }
+    }
+}
+
+/// Values with control characters, and values that would be mistaken for an encoded
+/// value, must be encoded
+fn needs_encoding(value: &str) -> bool {
+    value.chars().any(|c| c.is_ascii_control())
+        || value.starts_with(VERGEN_ESCAPED_MARKER)
+        || value.starts_with(VERGEN_BASE64_MARKER)
+}
+
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_ascii_control() => {
+                let _ = write!(escaped, "\\x{:02X}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverse [`escape`], returning `None` for an invalid escape sequence
+pub(crate) fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            't' => unescaped.push('\t'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16).ok()?;
+                unescaped.push(char::from(byte));
+            }
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(
+                    BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize],
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ValueEncoding, base64, escape, unescape};
+
+    #[test]
+    fn strip_works() {
+        assert_eq!("ab\rc", ValueEncoding::Strip.encode("a\nb\rc\n"));
+    }
+
+    #[test]
+    fn escaped_works() {
+        assert_eq!(r"a\nb\\c\r\t\x1B", escape("a\nb\\c\r\t\x1b"));
+        assert_eq!(
+            r"vergen-escaped:subject\n\nbody",
+            ValueEncoding::Escaped.encode("subject\n\nbody")
+        );
+        assert_eq!("plain", ValueEncoding::Escaped.encode("plain"));
+        assert_eq!(
+            "vergen-escaped:vergen-escaped:x",
+            ValueEncoding::Escaped.encode("vergen-escaped:x")
+        );
+    }
+
+    #[test]
+    fn unescape_works() {
+        let value = "a\nb\\c\r\t\x1b";
+        assert_eq!(Some(value.to_string()), unescape(&escape(value)));
+        assert_eq!(None, unescape(r"bad\q"));
+        assert_eq!(None, unescape(r"bad\"));
+    }
+
+    #[test]
+    fn base64_works() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+        assert_eq!("vergen-base64:YQpi", ValueEncoding::Base64.encode("a\nb"));
+        assert_eq!("plain", ValueEncoding::Base64.encode("plain"));
+    }
+
+    #[test]
+    fn vergen_pretty_decodes() {
+        for value in [
+            "subject\n\nbody",
+            "a\nb\\c\r\t\x1b",
+            "vergen-escaped:x",
+            "plain",
+        ] {
+            for encoding in [ValueEncoding::Escaped, ValueEncoding::Base64] {
+                assert_eq!(value, vergen_pretty::decode(&encoding.encode(value)));
+            }
+        }
+    }
+}
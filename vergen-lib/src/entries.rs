@@ -1,7 +1,15 @@
-use crate::VergenKey;
+use crate::{
+    CustomValue, Error, FailurePolicy, VergenKey,
+    names::KeyNames,
+    policy::KeyPolicies,
+    report::{Recorder, error_chain},
+};
 
-use anyhow::{Error, Result};
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    env,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
 
 /// The map used to emit `cargo:rustc-env=NAME=VALUE` cargo instructions
 pub type CargoRustcEnvMap = BTreeMap<VergenKey, String>;
@@ -48,6 +56,95 @@ impl DefaultConfig {
     }
 }
 
+/// The configuration of the [`Emitter`](crate::Emitter) adding an instruction set, given to
+/// [`add_map_entries`](Add::add_map_entries) and [`add_default_entries`](Add::add_default_entries)
+///
+/// Instruction generators look up the environment variable overrides of their keys here,
+/// so the overrides follow the [`key_prefix`](crate::Emitter::key_prefix) and
+/// [`rename_key`](crate::Emitter::rename_key) configuration of the emitter.  The default
+/// context uses the `VERGEN_*` names.
+///
+/// The context also records which keys were overridden, defaulted or left missing, which
+/// [`Emitter::report`](crate::Emitter::report) uses to describe each value.
+#[derive(Debug, Default)]
+pub struct EntryContext {
+    names: KeyNames,
+    policies: KeyPolicies,
+    recorder: Mutex<Recorder>,
+}
+
+impl EntryContext {
+    pub(crate) fn new(names: KeyNames, policies: KeyPolicies) -> Self {
+        Self {
+            names,
+            policies,
+            recorder: Mutex::default(),
+        }
+    }
+
+    fn recorder(&self) -> MutexGuard<'_, Recorder> {
+        self.recorder.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The key events recorded while the instruction set was added
+    pub(crate) fn into_recorded(self) -> Recorder {
+        self.recorder
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record that no value could be set for the key
+    pub(crate) fn record_missing(&self, key: VergenKey) {
+        self.recorder().missing.push(key);
+    }
+
+    /// Record that the key was set to the idempotent default
+    pub(crate) fn record_default(&self, key: VergenKey) {
+        let _new = self.recorder().defaulted.insert(key);
+    }
+
+    /// Record that generating the key failed with the given error
+    pub(crate) fn record_failure(&self, key: VergenKey, error: &Error) {
+        let _old = self.recorder().failed.insert(key, error_chain(error));
+    }
+
+    /// The [`key_policy`](crate::Emitter::key_policy) configured for the given key, if any
+    pub(crate) fn policy(&self, key: VergenKey) -> Option<FailurePolicy> {
+        self.policies.get(key)
+    }
+
+    /// The environment variable name for the given [`VergenKey`]
+    ///
+    /// # Example
+    /// ```
+    /// # use vergen_lib::{EntryContext, VergenKey};
+    /// let context = EntryContext::default();
+    #[cfg_attr(
+        feature = "build",
+        doc = r#"assert_eq!("VERGEN_BUILD_DATE", context.env_name(VergenKey::BuildDate));"#
+    )]
+    /// ```
+    #[must_use]
+    pub fn env_name(&self, key: VergenKey) -> String {
+        self.names.name(key)
+    }
+
+    /// The value of the environment variable overriding the given [`VergenKey`], if it is set
+    ///
+    /// The variable is named by [`env_name`](Self::env_name).  A
+    /// `cargo:rerun-if-env-changed` instruction is emitted for each variable read this way.
+    #[must_use]
+    pub fn env_override(&self, key: VergenKey) -> Option<String> {
+        let value = env::var(self.env_name(key)).ok();
+        let mut recorder = self.recorder();
+        let _new = recorder.consulted.insert(key);
+        if value.is_some() {
+            let _new = recorder.overrides.insert(key);
+        }
+        value
+    }
+}
+
 /// This trait should be implemented to allow the `vergen` emitter
 /// to properly emit instructions for your feature.
 pub trait Add {
@@ -56,46 +153,97 @@ pub trait Add {
     /// * Write to the `cargo_rustc_env` map to emit 'cargo:rustc-env=NAME=VALUE' instructions.
     /// * Write to the `cargo_rerun_if_changed` vector to emit 'cargo:rerun-if-changed=VALUE' instructions.
     /// * Write to the `cargo_warning` vector to emit 'cargo:warning=VALUE' instructions.
+    /// * Read the environment variable overrides with the `context`.
     ///
     /// # Errors
     ///
     /// If an error occurs, the `vergen` emitter will use `add_default_entries` to generate output.
-    /// This assumes generating instructions may fail in some manner so a [`anyhow::Result`] is returned.
+    /// This assumes generating instructions may fail in some manner so an [`Error`] is returned.
     ///
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()>;
+    ) -> Result<(), Error>;
 
     /// Based on the given configuration, emit either default idempotent output or generate a failue.
     ///
     /// * Write to the `cargo_rustc_env` map to emit 'cargo:rustc-env=NAME=VALUE' instructions.
     /// * Write to the `cargo_rerun_if_changed` vector to emit 'cargo:rerun-if-changed=VALUE' instructions.
     /// * Write to the `cargo_warning` vector to emit 'cargo:warning=VALUE' instructions.
+    /// * Read the environment variable overrides with the `context`.
     ///
     /// # Errors
     ///
-    /// This assumes generating instructions may fail in some manner so a [`anyhow::Result`] is returned.
+    /// If `fail_on_error` is configured, this should return the [`DefaultConfig::error`].
     ///
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()>;
+    ) -> Result<(), Error>;
+
+    /// The inputs that determine the output of [`add_map_entries`](Self::add_map_entries),
+    /// i.e. the [`file_stamp`](crate::file_stamp) of `.git/HEAD`, used to key the
+    /// [`Emitter::cache`](crate::Emitter::cache).
+    ///
+    /// The first input should identify the instruction set and its configuration.  Return
+    /// `None`, the default, if the output cannot be reused between build script runs.
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// The identity of the instruction set and its configuration, used to share its
+    /// output between the build scripts of a workspace with
+    /// [`Emitter::workspace_shared`](crate::Emitter::workspace_shared).
+    ///
+    /// Return `None`, the default, if the output is specific to the package being
+    /// built, i.e. its enabled features.
+    fn workspace_identity(&self) -> Option<String> {
+        None
+    }
+
+    /// The time of the checked out commit as Unix seconds, used by the `Build` instruction
+    /// set to derive the build timestamps from the commit when `SOURCE_DATE_EPOCH` is not
+    /// set.
+    ///
+    /// Return `Ok(None)`, the default, if the instruction set does not describe a
+    /// repository.
+    ///
+    /// # Errors
+    ///
+    /// * The repository or its checked out commit cannot be read.
+    ///
+    fn commit_time(&self) -> Result<Option<i64>, Error> {
+        Ok(None)
+    }
+
+    /// The environment variables the output of [`add_map_entries`](Self::add_map_entries)
+    /// is read from, other than the `VERGEN_*` overrides.  A `cargo:rerun-if-env-changed`
+    /// instruction is emitted for each, so the build script reruns when they change.
+    fn env_inputs(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
 }
 
 /// This trait should be implemented to allow the `vergen` emitter to properly emit your custom instructions.
 ///
+/// Values may be anything convertible into a [`CustomValue`], i.e. strings, but also
+/// flags, integers, timestamps and lists.  Typed values are emitted like the built-in keys
+/// of the same type, generate typed consts with
+/// [`Emitter::rust_source`](crate::Emitter::rust_source), and timestamps are set to the
+/// idempotent default when the emitter is idempotent.
+///
 /// # Example
 /// ```
-/// # use anyhow::Result;
 /// # use std::collections::BTreeMap;
-/// # use vergen_lib::{AddCustomEntries, CargoRerunIfChanged, CargoWarning, DefaultConfig};
+/// # use vergen_lib::{AddCustomEntries, CargoRerunIfChanged, CargoWarning, DefaultConfig, Error};
 /// #[derive(Default)]
 /// struct Custom {}
 ///
@@ -106,7 +254,7 @@ pub trait Add {
 ///         cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
 ///         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
 ///         cargo_warning: &mut CargoWarning,
-///     ) -> Result<()> {
+///     ) -> Result<(), Error> {
 ///         cargo_rustc_env_map.insert("vergen-cl", "custom_instruction");
 ///         cargo_warning.push("custom instruction generated".to_string());
 ///         Ok(())
@@ -118,7 +266,7 @@ pub trait Add {
 ///         _cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
 ///         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
 ///         _cargo_warning: &mut CargoWarning,
-///     ) -> Result<()> {
+///     ) -> Result<(), Error> {
 ///         Ok(())
 ///     }
 /// }
@@ -140,7 +288,7 @@ pub trait Add {
 ///     .add_custom_instructions(&Custom::default())?
 ///     .emit()
 /// ```
-pub trait AddCustom<K: Into<String> + Ord, V: Into<String>> {
+pub trait AddCustom<K: Into<String> + Ord, V: Into<CustomValue>> {
     /// Try to add instructions entries to the various given arguments.
     ///
     /// * Write to the `cargo_rustc_env` map to emit 'cargo:rustc-env=NAME=VALUE' instructions.
@@ -150,7 +298,7 @@ pub trait AddCustom<K: Into<String> + Ord, V: Into<String>> {
     /// # Errors
     ///
     /// If an error occurs, the `vergen` emitter will use `add_default_entries` to generate output.
-    /// This assumes generating instructions may fail in some manner so a [`anyhow::Result`] is returned.
+    /// This assumes generating instructions may fail in some manner so an [`Error`] is returned.
     ///
     fn add_calculated_entries(
         &self,
@@ -158,7 +306,7 @@ pub trait AddCustom<K: Into<String> + Ord, V: Into<String>> {
         cargo_rustc_env_map: &mut BTreeMap<K, V>,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()>;
+    ) -> Result<(), Error>;
 
     /// Based on the given configuration, emit either default idempotent output or generate a failue.
     ///
@@ -168,7 +316,7 @@ pub trait AddCustom<K: Into<String> + Ord, V: Into<String>> {
     ///
     /// # Errors
     ///
-    /// This assumes generating instructions may fail in some manner so a [`anyhow::Result`] is returned.
+    /// If `fail_on_error` is configured, this should return the [`DefaultConfig::error`].
     ///
     fn add_default_entries(
         &self,
@@ -176,13 +324,12 @@ pub trait AddCustom<K: Into<String> + Ord, V: Into<String>> {
         cargo_rustc_env_map: &mut BTreeMap<K, V>,
         cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()>;
+    ) -> Result<(), Error>;
 }
 
 #[doc(hidden)]
 pub(crate) mod test_gen {
-    use crate::{AddCustomEntries, CargoRerunIfChanged, CargoWarning};
-    use anyhow::{Result, anyhow};
+    use crate::{AddCustomEntries, CargoRerunIfChanged, CargoWarning, Error};
     use bon::Builder;
     use std::collections::BTreeMap;
 
@@ -200,9 +347,9 @@ pub(crate) mod test_gen {
             cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
             _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
             _cargo_warning: &mut CargoWarning,
-        ) -> Result<()> {
+        ) -> Result<(), Error> {
             if self.fail {
-                Err(anyhow!("We have failed"))
+                Err(Error::custom("We have failed"))
             } else {
                 if idempotent {
                     let _ = cargo_rustc_env_map.insert("test", "VERGEN_IDEMPOTENT_OUTPUT");
@@ -219,10 +366,9 @@ pub(crate) mod test_gen {
             cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
             _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
             _cargo_warning: &mut CargoWarning,
-        ) -> Result<()> {
+        ) -> Result<(), Error> {
             if *config.fail_on_error() {
-                let error = anyhow!(format!("{}", config.error()));
-                Err(error)
+                Err(config.error().clone())
             } else {
                 let _ = cargo_rustc_env_map.insert("test", "VERGEN_IDEMPOTENT_OUTPUT");
                 Ok(())
@@ -234,15 +380,38 @@ pub(crate) mod test_gen {
 #[cfg(test)]
 mod test {
     use super::DefaultConfig;
-    use anyhow::{Result, anyhow};
+    use crate::Error;
+    use anyhow::Result;
     use std::io::Write;
 
     #[test]
     fn default_config_debug() -> Result<()> {
-        let config = DefaultConfig::new(true, true, anyhow!("blah"));
+        let config = DefaultConfig::new(true, true, Error::custom("blah"));
         let mut buf = vec![];
         write!(buf, "{config:?}")?;
         assert_ne!(buf.len(), 0);
         Ok(())
     }
+
+    #[test]
+    #[serial_test::serial]
+    #[cfg(feature = "build")]
+    fn entry_context_names_follow_prefix() {
+        use super::EntryContext;
+        use crate::{VergenKey, names::KeyNames, policy::KeyPolicies};
+
+        let mut names = KeyNames::default();
+        names.set_prefix("MYAPP_");
+        let context = EntryContext::new(names, KeyPolicies::default());
+        assert_eq!("MYAPP_BUILD_DATE", context.env_name(VergenKey::BuildDate));
+        temp_env::with_var("MYAPP_BUILD_DATE", Some("a date"), || {
+            assert_eq!(
+                Some("a date".to_string()),
+                context.env_override(VergenKey::BuildDate)
+            );
+        });
+        temp_env::with_var("VERGEN_BUILD_DATE", Some("a date"), || {
+            assert!(context.env_override(VergenKey::BuildDate).is_none());
+        });
+    }
 }
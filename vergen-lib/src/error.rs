@@ -0,0 +1,230 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `vergen` errors
+
+use crate::VergenKey;
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    io,
+    sync::Arc,
+};
+
+/// The underlying cause of an [`Error`]
+pub type Source = Arc<dyn StdError + Send + Sync + 'static>;
+
+macro_rules! errors {
+    ($($(#[$attr:meta])* $variant:ident => $ctor:ident, $desc:literal;)*) => {
+        /// An error generated while adding or emitting `vergen` instructions
+        ///
+        /// Each variant carries the [`VergenKey`] being generated when the failure occurred,
+        /// if it is known, and the underlying cause.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// # use vergen_lib::Error;
+        /// #
+        /// fn is_fatal(error: &Error) -> bool {
+        ///     !matches!(error, Error::NotAGitRepo { .. } | Error::SysinfoUnavailable { .. })
+        /// }
+        /// ```
+        #[derive(Clone, Debug)]
+        #[non_exhaustive]
+        pub enum Error {
+            $(
+                $(#[$attr])*
+                $variant {
+                    /// The key being generated, if known
+                    key: Option<VergenKey>,
+                    /// The underlying cause
+                    source: Source,
+                },
+            )*
+        }
+
+        impl Error {
+            $(
+                #[doc = concat!("Create an [`Error::", stringify!($variant), "`] from the given cause")]
+                pub fn $ctor<E>(source: E) -> Self
+                where
+                    E: Into<Box<dyn StdError + Send + Sync + 'static>>,
+                {
+                    Error::$variant {
+                        key: None,
+                        source: Arc::from(source.into()),
+                    }
+                }
+            )*
+
+            /// The [`VergenKey`] being generated when the error occurred, if known
+            #[must_use]
+            pub fn key(&self) -> Option<VergenKey> {
+                match self {
+                    $(Error::$variant { key, .. })|* => *key,
+                }
+            }
+
+            /// Attach the given [`VergenKey`] to this error
+            #[must_use]
+            pub fn with_key(mut self, new_key: VergenKey) -> Self {
+                match &mut self {
+                    $(Error::$variant { key, .. })|* => *key = Some(new_key),
+                }
+                self
+            }
+
+            fn parts(&self) -> (&'static str, &Source) {
+                match self {
+                    $(Error::$variant { source, .. } => ($desc, source),)*
+                }
+            }
+        }
+    };
+}
+
+errors! {
+    /// The build is not running within a git repository or worktree
+    NotAGitRepo => not_a_git_repo, "not a git repository";
+    /// A git command or git library call failed
+    GitCommandFailed => git_command_failed, "git command failed";
+    /// The `rustc` version information could not be determined
+    RustcVersion => rustc_version, "unable to determine rustc version";
+    /// System information could not be determined
+    SysinfoUnavailable => sysinfo_unavailable, "system information unavailable";
+    /// `cargo` metadata or build script environment could not be read
+    CargoMetadata => cargo_metadata, "unable to read cargo metadata";
+    /// A date or timestamp could not be formatted
+    TimeFormat => time_format, "unable to format time";
+    /// An I/O error occurred
+    Io => io, "i/o error";
+    /// `SOURCE_DATE_EPOCH` is not a valid Unix timestamp
+    InvalidSourceDateEpoch => invalid_source_date_epoch, "invalid SOURCE_DATE_EPOCH";
+    /// The time zone is unknown or its offset cannot be determined
+    InvalidTimeZone => invalid_time_zone, "invalid time zone";
+    /// The same environment variable is emitted more than once
+    DuplicateKey => duplicate_key, "duplicate key";
+    /// An environment variable name is empty, or contains `=`, NUL or whitespace
+    InvalidKeyName => invalid_key_name, "invalid environment variable name";
+    /// The declarative configuration in `Cargo.toml` or `vergen.toml` is invalid
+    InvalidConfig => invalid_config, "invalid vergen configuration";
+    /// An error generated by a custom instruction generator, or any other failure
+    Custom => custom, "";
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (desc, source) = self.parts();
+        match (desc.is_empty(), self.key()) {
+            (true, None) => {}
+            (true, Some(key)) => write!(f, "{}: ", key.name())?,
+            (false, None) => write!(f, "{desc}: ")?,
+            (false, Some(key)) => write!(f, "{desc} ({}): ", key.name())?,
+        }
+        write!(f, "{source}")
+    }
+}
+
+impl StdError for Error {
+    /// The source of the wrapped error, as [`Display`] already includes the wrapped error
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        let (_, source) = self.parts();
+        source.source()
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::io(error)
+    }
+}
+
+impl Error {
+    /// Recover an [`Error`] that was propagated as a boxed error, or wrap any other
+    /// error with the given constructor, i.e. [`Error::git_command_failed`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error as StdError;
+    /// # use vergen_lib::Error;
+    /// #
+    /// let boxed: Box<dyn StdError + Send + Sync> = Error::rustc_version("no rustc").into();
+    /// let error = Error::downcast_or(boxed, Error::custom);
+    /// assert!(matches!(error, Error::RustcVersion { .. }));
+    ///
+    /// let boxed: Box<dyn StdError + Send + Sync> = "other".into();
+    /// let error = Error::downcast_or(boxed, Error::git_command_failed);
+    /// assert!(matches!(error, Error::GitCommandFailed { .. }));
+    /// ```
+    pub fn downcast_or<F>(error: Box<dyn StdError + Send + Sync + 'static>, f: F) -> Self
+    where
+        F: FnOnce(Box<dyn StdError + Send + Sync + 'static>) -> Self,
+    {
+        match error.downcast::<Error>() {
+            Ok(error) => *error,
+            Err(error) => f(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Error;
+    use std::{error::Error as StdError, io};
+
+    #[test]
+    fn display_works() {
+        let error = Error::git_command_failed("boom");
+        assert_eq!("git command failed: boom", error.to_string());
+        assert!(error.key().is_none());
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn source_skips_the_wrapped_error() {
+        let wrapped = anyhow::anyhow!("root cause").context("outer");
+        let error = Error::git_command_failed(wrapped);
+        assert_eq!("git command failed: outer", error.to_string());
+        assert_eq!(
+            Some("root cause".to_string()),
+            error.source().map(ToString::to_string)
+        );
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn with_key_works() {
+        use crate::VergenKey;
+        let error = Error::not_a_git_repo("no .git").with_key(VergenKey::GitSha);
+        assert_eq!(Some(VergenKey::GitSha), error.key());
+        assert_eq!(
+            "not a git repository (VERGEN_GIT_SHA): no .git",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn from_io_works() {
+        let error = Error::from(io::Error::other("disk"));
+        assert!(matches!(error, Error::Io { .. }));
+    }
+
+    #[test]
+    fn downcast_or_works() {
+        let boxed: Box<dyn StdError + Send + Sync> = Error::time_format("bad format").into();
+        let error = Error::downcast_or(boxed, Error::custom);
+        assert!(matches!(error, Error::TimeFormat { .. }));
+        let error = Error::downcast_or("other".into(), Error::custom);
+        assert!(matches!(error, Error::Custom { .. }));
+        assert_eq!("other", error.to_string());
+        let error = Error::downcast_or("other".into(), Error::rustc_version);
+        assert!(matches!(error, Error::RustcVersion { .. }));
+    }
+}
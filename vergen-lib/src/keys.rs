@@ -10,6 +10,7 @@
 #[cfg(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "git",
     feature = "rustc",
     feature = "si"
@@ -21,6 +22,10 @@ pub(crate) mod vergen_key {
     use crate::constants::{
         CARGO_DEBUG, CARGO_DEPENDENCIES, CARGO_FEATURES, CARGO_OPT_LEVEL, CARGO_TARGET_TRIPLE,
     };
+    #[cfg(feature = "ci")]
+    use crate::constants::{
+        CI_EVENT, CI_JOB_NAME, CI_PR_NUMBER, CI_PROVIDER, CI_RUN_ID, CI_RUN_URL,
+    };
     #[cfg(feature = "git")]
     use crate::constants::{
         GIT_BRANCH_NAME, GIT_COMMIT_AUTHOR_EMAIL, GIT_COMMIT_AUTHOR_NAME, GIT_COMMIT_COUNT,
@@ -65,6 +70,24 @@ pub(crate) mod vergen_key {
         /// The cargo dependencies (`VERGEN_CARGO_DEPENDENCIES`)
         #[cfg(feature = "cargo")]
         CargoDependencies,
+        /// The CI provider (`VERGEN_CI_PROVIDER`)
+        #[cfg(feature = "ci")]
+        CiProvider,
+        /// The CI pipeline or workflow run id (`VERGEN_CI_RUN_ID`)
+        #[cfg(feature = "ci")]
+        CiRunId,
+        /// The CI pipeline or workflow run URL (`VERGEN_CI_RUN_URL`)
+        #[cfg(feature = "ci")]
+        CiRunUrl,
+        /// The CI job name (`VERGEN_CI_JOB_NAME`)
+        #[cfg(feature = "ci")]
+        CiJobName,
+        /// The event that triggered the CI run (`VERGEN_CI_EVENT`)
+        #[cfg(feature = "ci")]
+        CiEvent,
+        /// The pull request number (`VERGEN_CI_PR_NUMBER`)
+        #[cfg(feature = "ci")]
+        CiPrNumber,
         /// The current working branch name (`VERGEN_GIT_BRANCH`)
         #[cfg(feature = "git")]
         GitBranch,
@@ -166,6 +189,18 @@ pub(crate) mod vergen_key {
                 VergenKey::CargoTargetTriple => CARGO_TARGET_TRIPLE,
                 #[cfg(feature = "cargo")]
                 VergenKey::CargoDependencies => CARGO_DEPENDENCIES,
+                #[cfg(feature = "ci")]
+                VergenKey::CiProvider => CI_PROVIDER,
+                #[cfg(feature = "ci")]
+                VergenKey::CiRunId => CI_RUN_ID,
+                #[cfg(feature = "ci")]
+                VergenKey::CiRunUrl => CI_RUN_URL,
+                #[cfg(feature = "ci")]
+                VergenKey::CiJobName => CI_JOB_NAME,
+                #[cfg(feature = "ci")]
+                VergenKey::CiEvent => CI_EVENT,
+                #[cfg(feature = "ci")]
+                VergenKey::CiPrNumber => CI_PR_NUMBER,
                 #[cfg(feature = "git")]
                 VergenKey::GitBranch => GIT_BRANCH_NAME,
                 #[cfg(feature = "git")]
@@ -220,6 +255,93 @@ pub(crate) mod vergen_key {
                 VergenKey::SysinfoCpuFrequency => SYSINFO_CPU_FREQUENCY,
             }
         }
+
+        /// Get every key available with the currently enabled features, in key order.
+        #[must_use]
+        pub fn all() -> &'static [VergenKey] {
+            &[
+                #[cfg(feature = "build")]
+                VergenKey::BuildDate,
+                #[cfg(feature = "build")]
+                VergenKey::BuildTimestamp,
+                #[cfg(feature = "build")]
+                VergenKey::BuildTimestampUnix,
+                #[cfg(feature = "cargo")]
+                VergenKey::CargoDebug,
+                #[cfg(feature = "cargo")]
+                VergenKey::CargoFeatures,
+                #[cfg(feature = "cargo")]
+                VergenKey::CargoOptLevel,
+                #[cfg(feature = "cargo")]
+                VergenKey::CargoTargetTriple,
+                #[cfg(feature = "cargo")]
+                VergenKey::CargoDependencies,
+                #[cfg(feature = "ci")]
+                VergenKey::CiProvider,
+                #[cfg(feature = "ci")]
+                VergenKey::CiRunId,
+                #[cfg(feature = "ci")]
+                VergenKey::CiRunUrl,
+                #[cfg(feature = "ci")]
+                VergenKey::CiJobName,
+                #[cfg(feature = "ci")]
+                VergenKey::CiEvent,
+                #[cfg(feature = "ci")]
+                VergenKey::CiPrNumber,
+                #[cfg(feature = "git")]
+                VergenKey::GitBranch,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitAuthorEmail,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitAuthorName,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitCount,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitDate,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitMessage,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitTimestamp,
+                #[cfg(feature = "git")]
+                VergenKey::GitCommitTimestampUnix,
+                #[cfg(feature = "git")]
+                VergenKey::GitDescribe,
+                #[cfg(feature = "git")]
+                VergenKey::GitSha,
+                #[cfg(feature = "git")]
+                VergenKey::GitDirty,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcChannel,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcCommitDate,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcCommitHash,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcHostTriple,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcLlvmVersion,
+                #[cfg(feature = "rustc")]
+                VergenKey::RustcSemver,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoName,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoOsVersion,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoUser,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoMemory,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoCpuVendor,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoCpuCoreCount,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoCpuName,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoCpuBrand,
+                #[cfg(feature = "si")]
+                VergenKey::SysinfoCpuFrequency,
+            ]
+        }
     }
 }
 
@@ -227,6 +349,7 @@ pub(crate) mod vergen_key {
 #[cfg(not(any(
     feature = "build",
     feature = "cargo",
+    feature = "ci",
     feature = "git",
     feature = "rustc",
     feature = "si"
@@ -247,6 +370,12 @@ pub(crate) mod vergen_key {
                 VergenKey::Empty => "",
             }
         }
+
+        /// Get every key available with the currently enabled features, in key order.
+        #[must_use]
+        pub fn all() -> &'static [VergenKey] {
+            &[]
+        }
     }
 }
 
@@ -255,6 +384,7 @@ pub(crate) mod vergen_key {
     not(any(
         feature = "build",
         feature = "cargo",
+        feature = "ci",
         feature = "git",
         feature = "rustc",
         feature = "si"
@@ -234,30 +234,80 @@
 #![cfg_attr(all(nightly, coverage_nightly), feature(coverage_attribute))]
 
 #[cfg(test)]
-use {temp_env as _, test_util as _};
+use {temp_env as _, test_util as _, vergen_pretty as _};
 
+mod cache;
+mod cfg;
 mod config;
 pub mod constants;
+mod custom;
+mod declarative;
 mod emitter;
+mod encoding;
 mod entries;
+mod error;
 mod keys;
+mod manifest;
+mod names;
+mod policy;
+#[cfg(feature = "redact")]
+mod redact;
+mod report;
+mod section;
+mod source;
+mod syntax;
+mod transform;
 mod utils;
+mod workspace;
+#[cfg(any(feature = "build", feature = "git"))]
+mod zone;
 
+pub use self::cache::file_digest;
+pub use self::cache::file_stamp;
+#[cfg(feature = "git")]
+pub use self::cache::git_stamps;
+pub use self::cfg::CfgKind;
 pub use self::config::Describe;
 pub use self::config::Dirty;
+pub use self::config::Granularity;
 pub use self::config::Sha;
+pub use self::config::TimeFormat;
+pub use self::custom::CustomValue;
+pub use self::declarative::FromManifest;
+pub use self::declarative::ManifestKeys;
 pub use self::emitter::Emitter;
+pub use self::encoding::ValueEncoding;
 pub use self::entries::Add as AddEntries;
 pub use self::entries::AddCustom as AddCustomEntries;
 pub use self::entries::CargoRerunIfChanged;
 pub use self::entries::CargoRustcEnvMap;
 pub use self::entries::CargoWarning;
 pub use self::entries::DefaultConfig;
+pub use self::entries::EntryContext;
 #[doc(hidden)]
 pub use self::entries::test_gen::CustomInsGen;
+pub use self::error::Error;
+pub use self::error::Source as ErrorSource;
 pub use self::keys::vergen_key::VergenKey;
+pub use self::manifest::ManifestFormat;
+pub use self::policy::CollisionPolicy;
+pub use self::policy::FailurePolicy;
+#[cfg(feature = "redact")]
+pub use self::redact::Redaction;
+pub use self::report::KeyStatus;
+pub use self::report::Report;
+pub use self::report::ReportEntry;
+pub use self::syntax::InstructionSyntax;
+pub use self::transform::Transform;
 pub use self::utils::add_default_map_entry;
 pub use self::utils::add_map_entry;
 pub use self::utils::count_idempotent;
+pub use self::utils::handle_key_failure;
 #[cfg(feature = "vcs_info")]
 pub use self::utils::vcs_info;
+#[cfg(any(feature = "build", feature = "git"))]
+pub use self::zone::TimeZone;
+#[cfg(any(feature = "build", feature = "git"))]
+pub use self::zone::VERGEN_TZ;
+#[cfg(any(feature = "build", feature = "git"))]
+pub use self::zone::in_time_zone;
@@ -0,0 +1,274 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Machine-readable export of the emitted key/value pairs
+
+use crate::KeyStatus;
+use std::{
+    fmt::Write as _,
+    io::{Result, Write},
+};
+
+/// The file format used when writing a manifest of the emitted values
+///
+/// See [`Emitter::manifest`](crate::Emitter::manifest).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ManifestFormat {
+    /// A JSON object keyed by variable name (`vergen.json`)
+    Json,
+    /// A TOML document with one table per variable name (`vergen.toml`)
+    Toml,
+    /// A shell-sourceable file of `NAME='value'` lines (`vergen.env`).  Entries whose names
+    /// are not shell identifiers, i.e. `vergen-cl`, are left out.
+    Dotenv,
+}
+
+impl ManifestFormat {
+    /// The file name used when the manifest is written under `OUT_DIR`
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ManifestFormat::Json => "vergen.json",
+            ManifestFormat::Toml => "vergen.toml",
+            ManifestFormat::Dotenv => "vergen.env",
+        }
+    }
+}
+
+/// The `source` written for an entry with the given status
+fn source(status: KeyStatus) -> &'static str {
+    match status {
+        // Missing entries have no value, and so are never written
+        KeyStatus::Computed | KeyStatus::Missing => "computed",
+        KeyStatus::EnvOverride => "overridden",
+        KeyStatus::Idempotent | KeyStatus::DefaultedOnError => "defaulted",
+    }
+}
+
+/// Escape a string as a double-quoted JSON or TOML basic string
+fn quoted(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        quoted(key)
+    }
+}
+
+/// Whether the name can be assigned by a POSIX shell, i.e. `[A-Za-z_][A-Za-z0-9_]*`
+fn is_shell_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn shell_quoted(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Write the given `(name, value, status)` entries in the requested format.
+pub(crate) fn write_manifest<'a, T, I>(
+    out: &mut T,
+    format: ManifestFormat,
+    entries: I,
+) -> Result<()>
+where
+    T: Write,
+    I: IntoIterator<Item = (&'a str, &'a str, KeyStatus)>,
+{
+    match format {
+        ManifestFormat::Json => {
+            let body = entries
+                .into_iter()
+                .map(|(name, value, status)| {
+                    format!(
+                        "  {}: {{ \"value\": {}, \"source\": \"{}\" }}",
+                        quoted(name),
+                        quoted(value),
+                        source(status)
+                    )
+                })
+                .collect::<Vec<String>>();
+            if body.is_empty() {
+                writeln!(out, "{{}}")?;
+            } else {
+                writeln!(out, "{{\n{}\n}}", body.join(",\n"))?;
+            }
+        }
+        ManifestFormat::Toml => {
+            for (idx, (name, value, status)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "[{}]", toml_key(name))?;
+                writeln!(out, "value = {}", quoted(value))?;
+                writeln!(out, "source = \"{}\"", source(status))?;
+            }
+        }
+        ManifestFormat::Dotenv => {
+            for (name, value, status) in entries
+                .into_iter()
+                .filter(|(name, _, _)| is_shell_name(name))
+            {
+                writeln!(out, "# {}", source(status))?;
+                writeln!(out, "{name}={}", shell_quoted(value))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        ManifestFormat, is_shell_name, quoted, shell_quoted, source, toml_key, write_manifest,
+    };
+    use crate::KeyStatus;
+    use anyhow::Result;
+
+    const ENTRIES: [(&str, &str, KeyStatus); 3] = [
+        ("test", "value", KeyStatus::Computed),
+        ("idem", "VERGEN_IDEMPOTENT_OUTPUT", KeyStatus::Idempotent),
+        ("over", "value", KeyStatus::EnvOverride),
+    ];
+
+    #[test]
+    fn quoted_works() {
+        assert_eq!(r#""a \"b\"\n\u0007""#, quoted("a \"b\"\n\u{7}"));
+    }
+
+    #[test]
+    fn toml_key_works() {
+        assert_eq!("VERGEN_GIT_SHA", toml_key("VERGEN_GIT_SHA"));
+        assert_eq!("vergen-cl", toml_key("vergen-cl"));
+        assert_eq!(r#""my key""#, toml_key("my key"));
+    }
+
+    #[test]
+    fn shell_quoted_works() {
+        assert_eq!(r"'it'\''s'", shell_quoted("it's"));
+    }
+
+    #[test]
+    fn is_shell_name_works() {
+        assert!(is_shell_name("VERGEN_GIT_SHA"));
+        assert!(is_shell_name("_private1"));
+        assert!(!is_shell_name("vergen-cl"));
+        assert!(!is_shell_name("1ST"));
+        assert!(!is_shell_name("MY.KEY"));
+        assert!(!is_shell_name(""));
+    }
+
+    #[test]
+    fn source_works() {
+        assert_eq!("computed", source(KeyStatus::Computed));
+        assert_eq!("overridden", source(KeyStatus::EnvOverride));
+        assert_eq!("defaulted", source(KeyStatus::Idempotent));
+        assert_eq!("defaulted", source(KeyStatus::DefaultedOnError));
+    }
+
+    #[test]
+    fn json_works() -> Result<()> {
+        let mut buf = vec![];
+        write_manifest(&mut buf, ManifestFormat::Json, ENTRIES)?;
+        let output = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            r#"{
+  "test": { "value": "value", "source": "computed" },
+  "idem": { "value": "VERGEN_IDEMPOTENT_OUTPUT", "source": "defaulted" },
+  "over": { "value": "value", "source": "overridden" }
+}
+"#,
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_json_works() -> Result<()> {
+        let mut buf = vec![];
+        write_manifest(&mut buf, ManifestFormat::Json, [])?;
+        assert_eq!("{}\n", String::from_utf8_lossy(&buf));
+        Ok(())
+    }
+
+    #[test]
+    fn toml_works() -> Result<()> {
+        let mut buf = vec![];
+        write_manifest(&mut buf, ManifestFormat::Toml, ENTRIES)?;
+        let output = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            r#"[test]
+value = "value"
+source = "computed"
+
+[idem]
+value = "VERGEN_IDEMPOTENT_OUTPUT"
+source = "defaulted"
+
+[over]
+value = "value"
+source = "overridden"
+"#,
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dotenv_works() -> Result<()> {
+        let mut buf = vec![];
+        write_manifest(&mut buf, ManifestFormat::Dotenv, ENTRIES)?;
+        let output = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            "# computed\ntest='value'\n# defaulted\nidem='VERGEN_IDEMPOTENT_OUTPUT'\n# overridden\nover='value'\n",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dotenv_skips_non_shell_names() -> Result<()> {
+        let mut buf = vec![];
+        let entries = [
+            ("vergen-cl", "value", KeyStatus::Computed),
+            ("VERGEN_CL", "value", KeyStatus::Computed),
+        ];
+        write_manifest(&mut buf, ManifestFormat::Dotenv, entries)?;
+        assert_eq!(
+            "# computed\nVERGEN_CL='value'\n",
+            String::from_utf8_lossy(&buf)
+        );
+        Ok(())
+    }
+}
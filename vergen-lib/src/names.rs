@@ -0,0 +1,78 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Configurable environment variable names for [`VergenKey`]s
+
+use crate::VergenKey;
+use std::collections::BTreeMap;
+
+/// The prefix and per-key renames used to name the emitted variables
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct KeyNames {
+    prefix: Option<String>,
+    renames: BTreeMap<VergenKey, String>,
+}
+
+impl KeyNames {
+    pub(crate) fn set_prefix(&mut self, prefix: &str) {
+        self.prefix = Some(prefix.trim_end_matches('_').to_string());
+    }
+
+    pub(crate) fn rename(&mut self, key: VergenKey, name: String) {
+        let _old = self.renames.insert(key, name);
+    }
+
+    /// The environment variable name for the given key
+    pub(crate) fn name(&self, key: VergenKey) -> String {
+        if let Some(name) = self.renames.get(&key) {
+            name.clone()
+        } else if let Some(prefix) = &self.prefix {
+            let name = key.name();
+            let suffix = name.strip_prefix("VERGEN").unwrap_or(name);
+            format!("{prefix}{suffix}")
+        } else {
+            key.name().to_string()
+        }
+    }
+}
+
+/// Whether the name can be set as an environment variable with `cargo:rustc-env`
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c == '=' || c == '\0' || c.is_whitespace())
+}
+
+#[cfg(all(test, feature = "build"))]
+mod test {
+    use super::KeyNames;
+    use crate::VergenKey;
+
+    #[test]
+    fn default_names_work() {
+        let names = KeyNames::default();
+        assert_eq!("VERGEN_BUILD_DATE", names.name(VergenKey::BuildDate));
+    }
+
+    #[test]
+    fn prefix_works() {
+        let mut names = KeyNames::default();
+        names.set_prefix("MYAPP_");
+        assert_eq!("MYAPP_BUILD_DATE", names.name(VergenKey::BuildDate));
+    }
+
+    #[test]
+    fn rename_overrides_prefix() {
+        let mut names = KeyNames::default();
+        names.set_prefix("MYAPP");
+        names.rename(VergenKey::BuildDate, "BUILT_ON".to_string());
+        assert_eq!("BUILT_ON", names.name(VergenKey::BuildDate));
+        assert_eq!(
+            "MYAPP_BUILD_TIMESTAMP",
+            names.name(VergenKey::BuildTimestamp)
+        );
+    }
+}
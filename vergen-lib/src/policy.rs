@@ -0,0 +1,91 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Failure policies for instruction sets and individual keys
+
+use crate::VergenKey;
+use std::collections::BTreeMap;
+
+/// What to do when generating an instruction set, or a single key, fails
+///
+/// See [`Emitter::add_instructions_with_policy`](crate::Emitter::add_instructions_with_policy)
+/// and [`Emitter::key_policy`](crate::Emitter::key_policy).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum FailurePolicy {
+    /// Fail the build, as if [`fail_on_error`](crate::Emitter::fail_on_error) were set
+    Fail,
+    /// Emit the idempotent default, as if [`default_on_error`](crate::Emitter::default_on_error) were set
+    Default,
+    /// Emit nothing and generate no warnings
+    Skip,
+}
+
+/// What to do when the same environment variable is emitted more than once, or an
+/// environment variable name is invalid
+///
+/// See [`Emitter::collision_policy`](crate::Emitter::collision_policy).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CollisionPolicy {
+    /// Emit a `cargo:warning` instruction, the last value added wins
+    #[default]
+    Warn,
+    /// Fail the build
+    Error,
+}
+
+/// The per-key failure policies configured on an [`Emitter`](crate::Emitter)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct KeyPolicies(BTreeMap<VergenKey, FailurePolicy>);
+
+impl KeyPolicies {
+    pub(crate) fn set(&mut self, key: VergenKey, policy: FailurePolicy) {
+        let _old = self.0.insert(key, policy);
+    }
+
+    pub(crate) fn get(&self, key: VergenKey) -> Option<FailurePolicy> {
+        self.0.get(&key).copied()
+    }
+}
+
+#[cfg(all(test, feature = "git"))]
+mod test {
+    use super::{FailurePolicy, KeyPolicies};
+    use crate::{EntryContext, Error, VergenKey, handle_key_failure, names::KeyNames};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn context_policy_works() {
+        let mut policies = KeyPolicies::default();
+        policies.set(VergenKey::GitDescribe, FailurePolicy::Skip);
+        let context = EntryContext::new(KeyNames::default(), policies);
+        let mut map = BTreeMap::new();
+        let mut warnings = vec![];
+        let error = Error::custom("no tags");
+        assert!(
+            handle_key_failure(
+                &context,
+                VergenKey::GitDescribe,
+                error,
+                &mut map,
+                &mut warnings
+            )
+            .is_ok()
+        );
+        assert!(map.is_empty());
+        let error = Error::custom("no sha");
+        assert!(
+            handle_key_failure(&context, VergenKey::GitSha, error, &mut map, &mut warnings)
+                .is_err()
+        );
+        assert!(
+            EntryContext::default()
+                .policy(VergenKey::GitDescribe)
+                .is_none()
+        );
+    }
+}
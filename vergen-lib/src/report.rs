@@ -0,0 +1,276 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Provenance of the emitted values
+
+use crate::{Error, VergenKey};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error as _,
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+/// How the final value of an entry was produced
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum KeyStatus {
+    /// The value was generated by `vergen`
+    Computed,
+    /// The value was taken from an environment variable override
+    EnvOverride,
+    /// The value is the idempotent default because idempotent output was requested
+    Idempotent,
+    /// The value is the idempotent default because generating it failed
+    DefaultedOnError,
+    /// Generating the value failed and no value is emitted
+    Missing,
+}
+
+impl KeyStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            KeyStatus::Computed => "computed",
+            KeyStatus::EnvOverride => "env override",
+            KeyStatus::Idempotent => "idempotent",
+            KeyStatus::DefaultedOnError => "defaulted on error",
+            KeyStatus::Missing => "missing",
+        }
+    }
+}
+
+/// A single requested key in a [`Report`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportEntry {
+    name: String,
+    key: Option<VergenKey>,
+    value: Option<String>,
+    status: KeyStatus,
+    errors: Vec<String>,
+    elapsed: Duration,
+}
+
+impl ReportEntry {
+    /// The emitted variable name
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The [`VergenKey`], or `None` for custom and derived keys
+    #[must_use]
+    pub fn key(&self) -> Option<VergenKey> {
+        self.key
+    }
+    /// The emitted value, or `None` if the key is [`KeyStatus::Missing`]
+    #[must_use]
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+    /// How the value was produced
+    #[must_use]
+    pub fn status(&self) -> KeyStatus {
+        self.status
+    }
+    /// The chain of errors, outermost first, that caused the value to be defaulted or missing
+    #[must_use]
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+    /// The time spent generating the instruction set that produced this value
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// The provenance of every requested key, see [`Emitter::report`](crate::Emitter::report)
+///
+/// The [`Display`] implementation renders one line per entry, followed by any errors,
+/// which is suitable for writing to a file or the build script output.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    /// The entries, ordered by the built-in keys and then the custom keys
+    #[must_use]
+    pub fn entries(&self) -> &[ReportEntry] {
+        &self.entries
+    }
+
+    /// The entry for the given emitted variable name
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&ReportEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// The entries with the given status
+    pub fn with_status(&self, status: KeyStatus) -> impl Iterator<Item = &ReportEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.status == status)
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        name: String,
+        key: Option<VergenKey>,
+        value: Option<String>,
+        status: KeyStatus,
+        provenance: Option<&Provenance>,
+    ) {
+        let (errors, elapsed) = provenance.map_or_else(
+            || (vec![], Duration::ZERO),
+            |p| (p.errors.clone(), p.elapsed),
+        );
+        self.entries.push(ReportEntry {
+            name,
+            key,
+            value,
+            status,
+            errors,
+            elapsed,
+        });
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{}: {}", entry.name, entry.status.as_str())?;
+            if let Some(value) = &entry.value {
+                write!(f, " = {value}")?;
+            }
+            writeln!(f, " ({:?})", entry.elapsed)?;
+            for error in &entry.errors {
+                writeln!(f, "    caused by: {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What was recorded about a key while its instruction set was generated
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct Provenance {
+    pub(crate) errors: Vec<String>,
+    pub(crate) elapsed: Duration,
+    pub(crate) failed: bool,
+    pub(crate) missing: bool,
+    pub(crate) overridden: bool,
+    pub(crate) defaulted: bool,
+}
+
+impl Provenance {
+    /// How the value was produced, given what was recorded while generating it
+    pub(crate) fn status(&self) -> KeyStatus {
+        if self.missing {
+            KeyStatus::Missing
+        } else if self.overridden {
+            KeyStatus::EnvOverride
+        } else if self.defaulted && self.failed {
+            KeyStatus::DefaultedOnError
+        } else if self.defaulted {
+            KeyStatus::Idempotent
+        } else {
+            KeyStatus::Computed
+        }
+    }
+}
+
+/// The key events recorded by the instruction generators through the
+/// [`EntryContext`](crate::EntryContext)
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    /// The keys for which no value could be set
+    pub(crate) missing: Vec<VergenKey>,
+    /// The keys that failed to generate, with their error chains
+    pub(crate) failed: BTreeMap<VergenKey, Vec<String>>,
+    /// The keys whose override environment variable was read
+    pub(crate) consulted: BTreeSet<VergenKey>,
+    /// The keys whose value was taken from their override environment variable
+    pub(crate) overrides: BTreeSet<VergenKey>,
+    /// The keys set to the idempotent default
+    pub(crate) defaulted: BTreeSet<VergenKey>,
+}
+
+/// The error followed by each of its sources.  The message of an [`Error`] already
+/// includes the error it wraps, so the chain continues with the source of that error.
+pub(crate) fn error_chain(error: &Error) -> Vec<String> {
+    let mut chain = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(cause) = source {
+        chain.push(cause.to_string());
+        source = cause.source();
+    }
+    chain
+}
+
+#[cfg(all(test, feature = "build"))]
+mod test {
+    use super::{KeyStatus, Report, error_chain};
+    use crate::{EntryContext, Error, VergenKey};
+
+    #[test]
+    #[serial_test::serial]
+    fn recorder_works() {
+        let context = EntryContext::default();
+        context.record_missing(VergenKey::BuildDate);
+        let error = Error::time_format("bad").with_key(VergenKey::BuildTimestamp);
+        context.record_failure(VergenKey::BuildTimestamp, &error);
+        temp_env::with_var("VERGEN_BUILD_DATE", Some("a date"), || {
+            assert!(context.env_override(VergenKey::BuildDate).is_some());
+        });
+        temp_env::with_var_unset("VERGEN_BUILD_TIMESTAMP", || {
+            assert!(context.env_override(VergenKey::BuildTimestamp).is_none());
+        });
+        let recorded = context.into_recorded();
+        assert_eq!(vec![VergenKey::BuildDate], recorded.missing);
+        assert!(recorded.overrides.contains(&VergenKey::BuildDate));
+        assert!(!recorded.overrides.contains(&VergenKey::BuildTimestamp));
+        assert!(recorded.consulted.contains(&VergenKey::BuildTimestamp));
+        assert_eq!(
+            Some(&vec![
+                "unable to format time (VERGEN_BUILD_TIMESTAMP): bad".to_string()
+            ]),
+            recorded.failed.get(&VergenKey::BuildTimestamp)
+        );
+    }
+
+    #[test]
+    fn error_chain_works() {
+        let wrapped = anyhow::anyhow!("root cause").context("outer");
+        let error = Error::git_command_failed(wrapped).with_key(VergenKey::BuildDate);
+        assert_eq!(
+            vec![
+                "git command failed (VERGEN_BUILD_DATE): outer".to_string(),
+                "root cause".to_string()
+            ],
+            error_chain(&error)
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        let mut report = Report::default();
+        report.push(
+            "VERGEN_BUILD_DATE".to_string(),
+            Some(VergenKey::BuildDate),
+            Some("2024-01-01".to_string()),
+            KeyStatus::Computed,
+            None,
+        );
+        assert_eq!(
+            "VERGEN_BUILD_DATE: computed = 2024-01-01 (0ns)\n",
+            report.to_string()
+        );
+        assert_eq!(1, report.with_status(KeyStatus::Computed).count());
+        assert!(report.get("VERGEN_BUILD_DATE").is_some());
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Generation of a Rust source file embedding the values in a dedicated link section
+
+use crate::{encoding::escape, names::is_valid_name};
+use std::io::{Result, Write};
+
+/// The first line of the embedded payload, identifying the format
+const HEADER: &str = "vergen-info 1";
+
+/// The Mach-O equivalent of the link section, as Mach-O section names are
+/// qualified by their segment
+const MACHO_SECTION: &str = "__DATA,__vergen_info";
+
+/// Serialize the entries as the header line followed by a `NAME=value` line per entry,
+/// with the values escaped so that each entry is a single line.  Entries whose name is
+/// not a valid environment variable name are skipped.
+fn payload<'a, I>(entries: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut payload = format!("{HEADER}\n");
+    for (name, value) in entries.into_iter().filter(|(name, _)| is_valid_name(name)) {
+        payload.push_str(name);
+        payload.push('=');
+        payload.push_str(&escape(value));
+        payload.push('\n');
+    }
+    payload.into_bytes()
+}
+
+/// Write a `#[used]` static holding the serialized entries in the given link section.
+pub(crate) fn write_link_section<'a, T, I>(out: &mut T, section: &str, entries: I) -> Result<()>
+where
+    T: Write,
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let payload = payload(entries);
+    writeln!(out, "// This file was generated by vergen.  Do not edit.")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "/// The `vergen` values, embedded in the `{section}` link section"
+    )?;
+    writeln!(out, "#[allow(dead_code, unsafe_code)]")?;
+    writeln!(out, "#[used]")?;
+    writeln!(
+        out,
+        "#[cfg_attr(not(target_vendor = \"apple\"), unsafe(link_section = {section:?}))]"
+    )?;
+    writeln!(
+        out,
+        "#[cfg_attr(target_vendor = \"apple\", unsafe(link_section = {MACHO_SECTION:?}))]"
+    )?;
+    writeln!(
+        out,
+        "pub static VERGEN_INFO: [u8; {}] = *b\"{}\";",
+        payload.len(),
+        payload.escape_ascii()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{payload, write_link_section};
+    use anyhow::Result;
+
+    #[test]
+    fn payload_works() {
+        let entries = [
+            ("VERGEN_GIT_SHA", "abc"),
+            ("CUSTOM", "multi\nline"),
+            ("not valid", "skipped"),
+        ];
+        assert_eq!(
+            b"vergen-info 1\nVERGEN_GIT_SHA=abc\nCUSTOM=multi\\nline\n".to_vec(),
+            payload(entries)
+        );
+    }
+
+    #[test]
+    fn link_section_is_written() -> Result<()> {
+        let mut buf = vec![];
+        write_link_section(&mut buf, ".vergen_info", [("A", "\"quoted\"")])?;
+        let output = String::from_utf8_lossy(&buf);
+        assert!(output.contains("unsafe(link_section = \".vergen_info\"))]"));
+        assert!(
+            output.contains(
+                r#"pub static VERGEN_INFO: [u8; 25] = *b"vergen-info 1\nA=\"quoted\"\n";"#
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Generation of a Rust source file containing typed `pub const` items
+
+use crate::{
+    CargoRustcEnvMap, Error, VergenKey, constants::VERGEN_IDEMPOTENT_DEFAULT, names::KeyNames,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Result, Write},
+};
+
+/// The Rust type a value is rendered as in the generated source
+// Not every kind is used with every feature combination
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ValueKind {
+    Str,
+    Bool,
+    U64,
+    I64,
+    List,
+}
+
+impl ValueKind {
+    fn of(key: VergenKey) -> Self {
+        match key {
+            #[cfg(feature = "build")]
+            VergenKey::BuildTimestampUnix => ValueKind::I64,
+            #[cfg(feature = "cargo")]
+            VergenKey::CargoDebug => ValueKind::Bool,
+            #[cfg(feature = "cargo")]
+            VergenKey::CargoFeatures | VergenKey::CargoDependencies => ValueKind::List,
+            #[cfg(feature = "ci")]
+            VergenKey::CiPrNumber => ValueKind::U64,
+            #[cfg(feature = "git")]
+            VergenKey::GitCommitCount => ValueKind::U64,
+            #[cfg(feature = "git")]
+            VergenKey::GitCommitTimestampUnix => ValueKind::I64,
+            #[cfg(feature = "git")]
+            VergenKey::GitDirty => ValueKind::Bool,
+            #[cfg(feature = "si")]
+            VergenKey::SysinfoCpuCoreCount | VergenKey::SysinfoCpuFrequency => ValueKind::U64,
+            #[cfg(feature = "si")]
+            VergenKey::SysinfoCpuName => ValueKind::List,
+            _ => ValueKind::Str,
+        }
+    }
+
+    fn type_name(self) -> &'static str {
+        match self {
+            ValueKind::Str => "&str",
+            ValueKind::Bool => "bool",
+            ValueKind::U64 => "u64",
+            ValueKind::I64 => "i64",
+            ValueKind::List => "&[&str]",
+        }
+    }
+
+    /// Render the value as a Rust literal, or `None` if it does not parse as this kind.
+    fn literal(self, value: &str) -> Option<String> {
+        match self {
+            ValueKind::Str => Some(format!("{value:?}")),
+            ValueKind::Bool => value.parse::<bool>().ok().map(|v| v.to_string()),
+            ValueKind::U64 => value.parse::<u64>().ok().map(|v| v.to_string()),
+            ValueKind::I64 => value.parse::<i64>().ok().map(|v| v.to_string()),
+            ValueKind::List => {
+                let items = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| format!("{item:?}"))
+                    .collect::<Vec<String>>();
+                Some(format!("&[{}]", items.join(", ")))
+            }
+        }
+    }
+}
+
+/// Convert an arbitrary key into a valid Rust `SCREAMING_SNAKE_CASE` identifier
+fn const_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// The const name generated for a built-in key
+fn key_const_name(key: VergenKey) -> String {
+    const_name(key.name().trim_start_matches("VERGEN_"))
+}
+
+/// Find the custom entries whose const name is already generated for a built-in key,
+/// or for another custom entry, i.e. `GIT_SHA` or `a-b` next to `a_b`.
+///
+/// The generated source would not compile, so these entries are not written by
+/// [`write_rust_source`].
+pub(crate) fn const_collisions(
+    cargo_rustc_env_map_custom: &BTreeMap<String, String>,
+) -> Vec<Error> {
+    let mut names = VergenKey::all()
+        .iter()
+        .map(|key| (key_const_name(*key), key.name()))
+        .collect::<BTreeMap<String, &str>>();
+    let mut errors = vec![];
+    for key in cargo_rustc_env_map_custom.keys() {
+        let name = const_name(key);
+        if let Some(other) = names.get(&name) {
+            let message = format!("{key} generates the const {name}, as does {other}");
+            errors.push(Error::duplicate_key(message));
+        } else {
+            let _old = names.insert(name, key);
+        }
+    }
+    errors
+}
+
+fn write_const<T>(
+    out: &mut T,
+    env_name: &str,
+    const_name: &str,
+    kind: ValueKind,
+    value: Option<&String>,
+) -> Result<()>
+where
+    T: Write,
+{
+    let literal = value
+        .filter(|v| *v != VERGEN_IDEMPOTENT_DEFAULT)
+        .and_then(|v| kind.literal(v))
+        .map_or_else(|| "None".to_string(), |lit| format!("Some({lit})"));
+    writeln!(out, "/// `{env_name}`")?;
+    writeln!(out, "#[allow(dead_code)]")?;
+    writeln!(
+        out,
+        "pub const {const_name}: Option<{}> = {literal};",
+        kind.type_name()
+    )?;
+    Ok(())
+}
+
+/// Write the typed `pub const` items for every known [`VergenKey`] and custom entry.
+///
+/// Keys that were not requested, could not be generated, were set to the
+/// idempotent default, or whose value does not parse as the expected type
+/// are written as `None`.  Custom entries whose const name is already written are
+/// skipped, see [`const_collisions`].
+pub(crate) fn write_rust_source<T>(
+    out: &mut T,
+    key_names: &KeyNames,
+    cargo_rustc_env_map: &CargoRustcEnvMap,
+    cargo_rustc_env_map_custom: &BTreeMap<String, String>,
+    custom_kinds: &BTreeMap<String, ValueKind>,
+) -> Result<()>
+where
+    T: Write,
+{
+    writeln!(out, "// This file was generated by vergen.  Do not edit.")?;
+    let mut written = BTreeSet::new();
+    for key in VergenKey::all() {
+        writeln!(out)?;
+        let name = key_const_name(*key);
+        write_const(
+            out,
+            &key_names.name(*key),
+            &name,
+            ValueKind::of(*key),
+            cargo_rustc_env_map.get(key),
+        )?;
+        let _new = written.insert(name);
+    }
+    for (key, value) in cargo_rustc_env_map_custom {
+        let name = const_name(key);
+        if written.contains(&name) {
+            continue;
+        }
+        writeln!(out)?;
+        let kind = custom_kinds.get(key).copied().unwrap_or(ValueKind::Str);
+        write_const(out, key, &name, kind, Some(value))?;
+        let _new = written.insert(name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ValueKind, const_collisions, const_name, write_rust_source};
+    use crate::names::KeyNames;
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn const_name_works() {
+        assert_eq!("VERGEN_CL", const_name("vergen-cl"));
+        assert_eq!("_1ST", const_name("1st"));
+        assert_eq!("_", const_name(""));
+    }
+
+    #[test]
+    fn literal_works() {
+        assert_eq!(
+            Some("\"a \\\"b\\\"\\n\"".to_string()),
+            ValueKind::Str.literal("a \"b\"\n")
+        );
+        assert_eq!(Some("true".to_string()), ValueKind::Bool.literal("true"));
+        assert_eq!(None, ValueKind::Bool.literal("yes"));
+        assert_eq!(Some("42".to_string()), ValueKind::U64.literal("42"));
+        assert_eq!(None, ValueKind::U64.literal("-1"));
+        assert_eq!(Some("-1".to_string()), ValueKind::I64.literal("-1"));
+        assert_eq!(
+            Some("&[\"build\", \"git\"]".to_string()),
+            ValueKind::List.literal("build,git")
+        );
+        assert_eq!(Some("&[]".to_string()), ValueKind::List.literal(""));
+    }
+
+    #[test]
+    fn custom_entries_are_written() -> Result<()> {
+        let mut custom = BTreeMap::new();
+        let _old = custom.insert("test".to_string(), "value".to_string());
+        let _old = custom.insert("idem".to_string(), "VERGEN_IDEMPOTENT_OUTPUT".to_string());
+        let _old = custom.insert("count".to_string(), "42".to_string());
+        let mut kinds = BTreeMap::new();
+        let _old = kinds.insert("count".to_string(), ValueKind::I64);
+        let mut buf = vec![];
+        write_rust_source(
+            &mut buf,
+            &KeyNames::default(),
+            &BTreeMap::new(),
+            &custom,
+            &kinds,
+        )?;
+        let output = String::from_utf8_lossy(&buf);
+        assert!(output.contains("pub const TEST: Option<&str> = Some(\"value\");"));
+        assert!(output.contains("pub const IDEM: Option<&str> = None;"));
+        assert!(output.contains("pub const COUNT: Option<i64> = Some(42);"));
+        Ok(())
+    }
+
+    #[test]
+    fn const_collisions_are_detected() -> Result<()> {
+        let mut custom = BTreeMap::new();
+        let _old = custom.insert("a-b".to_string(), "first".to_string());
+        let _old = custom.insert("a_b".to_string(), "second".to_string());
+        let _old = custom.insert("test".to_string(), "value".to_string());
+        let errors = const_collisions(&custom);
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            "duplicate key: a_b generates the const A_B, as does a-b",
+            errors[0].to_string()
+        );
+        let mut buf = vec![];
+        write_rust_source(
+            &mut buf,
+            &KeyNames::default(),
+            &BTreeMap::new(),
+            &custom,
+            &BTreeMap::new(),
+        )?;
+        let output = String::from_utf8_lossy(&buf);
+        assert_eq!(1, output.matches("pub const A_B:").count());
+        assert!(output.contains("pub const A_B: Option<&str> = Some(\"first\");"));
+        Ok(())
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn built_in_const_collisions_are_detected() {
+        let mut custom = BTreeMap::new();
+        let _old = custom.insert("GIT_SHA".to_string(), "value".to_string());
+        let errors = const_collisions(&custom);
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            "duplicate key: GIT_SHA generates the const GIT_SHA, as does VERGEN_GIT_SHA",
+            errors[0].to_string()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Selection of the cargo build script instruction syntax
+
+use std::{env, process::Command};
+
+/// The first cargo release that understands the `cargo::` instruction syntax
+const MODERN_MINOR: u32 = 77;
+
+/// The syntax used for the emitted cargo build script instructions
+///
+/// See [`Emitter::instruction_syntax`](crate::Emitter::instruction_syntax).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum InstructionSyntax {
+    /// The single-colon form, i.e. `cargo:rustc-env=VAR=VALUE`
+    #[default]
+    Legacy,
+    /// The double-colon form, i.e. `cargo::rustc-env=VAR=VALUE`, supported by cargo 1.77+
+    Modern,
+    /// Use [`Modern`](Self::Modern) if the toolchain and the package `rust-version` support
+    /// it, [`Legacy`](Self::Legacy) otherwise
+    ///
+    /// Cargo rejects `cargo::` instructions from a package whose `rust-version` is below
+    /// 1.77, whichever toolchain is running.  The `rust-version` is read from the
+    /// `CARGO_PKG_RUST_VERSION` environment variable, and the toolchain version from the
+    /// `rustc` given by the `RUSTC` environment variable, both set by cargo for build
+    /// scripts.  The syntax is resolved once each time the instructions are emitted.
+    Auto,
+}
+
+impl InstructionSyntax {
+    /// Resolve [`Auto`](Self::Auto) to a concrete syntax
+    pub(crate) fn resolve(self) -> Self {
+        match self {
+            InstructionSyntax::Auto => {
+                if package_minor().is_none_or(|minor| minor >= MODERN_MINOR)
+                    && toolchain_minor().is_some_and(|minor| minor >= MODERN_MINOR)
+                {
+                    InstructionSyntax::Modern
+                } else {
+                    InstructionSyntax::Legacy
+                }
+            }
+            syntax => syntax,
+        }
+    }
+
+    /// The instruction prefix, i.e. `cargo:` or `cargo::`, for a resolved syntax
+    pub(crate) fn prefix(self) -> &'static str {
+        match self {
+            InstructionSyntax::Modern => "cargo::",
+            InstructionSyntax::Legacy | InstructionSyntax::Auto => "cargo:",
+        }
+    }
+}
+
+/// The minor version of the package `rust-version`, if it is set
+fn package_minor() -> Option<u32> {
+    parse_minor(env::var("CARGO_PKG_RUST_VERSION").ok()?.trim())
+}
+
+/// The minor version of the toolchain, from `rustc -V` output, i.e.
+/// `rustc 1.77.0 (aedd173a2 2024-03-17)`
+fn toolchain_minor() -> Option<u32> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc).arg("-V").output().ok()?;
+    if output.status.success() {
+        parse_minor(
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .nth(1)?,
+        )
+    } else {
+        None
+    }
+}
+
+/// Parse the minor version from a version, i.e. `1.77.0`, `1.90.0-nightly` or `1.70`
+fn parse_minor(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '-']);
+    match (parts.next()?, parts.next()?) {
+        ("1", minor) => minor.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InstructionSyntax, parse_minor};
+    use serial_test::serial;
+    use temp_env::{with_var, with_vars};
+
+    #[test]
+    fn parse_minor_works() {
+        assert_eq!(Some(77), parse_minor("1.77.0"));
+        assert_eq!(Some(90), parse_minor("1.90.0-nightly"));
+        assert_eq!(Some(70), parse_minor("1.70"));
+        assert_eq!(None, parse_minor(""));
+        assert_eq!(None, parse_minor("2.0.0"));
+    }
+
+    #[test]
+    fn prefix_works() {
+        assert_eq!("cargo:", InstructionSyntax::Legacy.prefix());
+        assert_eq!("cargo::", InstructionSyntax::Modern.prefix());
+    }
+
+    #[test]
+    #[serial]
+    fn auto_resolves_modern() {
+        // The toolchain running the tests is newer than the MSRV, which supports `cargo::`
+        with_vars(
+            [("RUSTC", None), ("CARGO_PKG_RUST_VERSION", Some("1.77"))],
+            || {
+                assert_eq!(InstructionSyntax::Modern, InstructionSyntax::Auto.resolve());
+            },
+        );
+        with_vars(
+            [("RUSTC", None::<&str>), ("CARGO_PKG_RUST_VERSION", None)],
+            || {
+                assert_eq!(InstructionSyntax::Modern, InstructionSyntax::Auto.resolve());
+            },
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn auto_falls_back_to_legacy() {
+        with_var("RUSTC", Some("this_is_not_rustc"), || {
+            assert_eq!(InstructionSyntax::Legacy, InstructionSyntax::Auto.resolve());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn auto_falls_back_to_legacy_for_rust_version() {
+        // Cargo rejects `cargo::` from a package with an older `rust-version`
+        with_vars(
+            [("RUSTC", None), ("CARGO_PKG_RUST_VERSION", Some("1.70"))],
+            || {
+                assert_eq!(InstructionSyntax::Legacy, InstructionSyntax::Auto.resolve());
+            },
+        );
+    }
+}
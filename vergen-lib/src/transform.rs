@@ -0,0 +1,149 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Value transformers and templated derived keys
+
+use crate::constants::VERGEN_IDEMPOTENT_DEFAULT;
+
+/// A transformation applied to a value before it is emitted
+///
+/// See [`Emitter::transform`](crate::Emitter::transform).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Transform {
+    /// Keep at most the given number of characters, i.e. a short SHA
+    Truncate(usize),
+    /// Remove the given prefix if present, i.e. `v` from `v1.2.3`
+    StripPrefix(String),
+    /// Lowercase the value and replace runs of non-alphanumeric characters with `-`,
+    /// i.e. `feature/My_Branch` becomes `feature-my-branch`
+    Slugify,
+    /// Convert the value to lowercase
+    Lowercase,
+    /// Convert the value to uppercase
+    Uppercase,
+}
+
+impl Transform {
+    /// Apply the transformation.  The idempotent default is never transformed.
+    pub(crate) fn apply(&self, value: &str) -> String {
+        if value == VERGEN_IDEMPOTENT_DEFAULT {
+            return value.to_string();
+        }
+        match self {
+            Transform::Truncate(len) => value.chars().take(*len).collect(),
+            Transform::StripPrefix(prefix) => value
+                .strip_prefix(prefix.as_str())
+                .unwrap_or(value)
+                .to_string(),
+            Transform::Slugify => slugify(value),
+            Transform::Lowercase => value.to_lowercase(),
+            Transform::Uppercase => value.to_uppercase(),
+        }
+    }
+}
+
+fn slugify(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Render a template of the form `{NAME}+{OTHER}` using the given lookup.
+///
+/// `{{` and `}}` are literal braces.  On failure the unresolved name is returned.
+pub(crate) fn render<F>(template: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                let _brace = chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                let _brace = chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for n in chars.by_ref() {
+                    if n == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(n);
+                }
+                if !closed {
+                    return Err(format!("{{{name}"));
+                }
+                out.push_str(&lookup(name.trim()).ok_or(name)?);
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Transform, render, slugify};
+
+    #[test]
+    fn transforms_work() {
+        assert_eq!("abcdef1", Transform::Truncate(7).apply("abcdef1234567890"));
+        assert_eq!("ab", Transform::Truncate(7).apply("ab"));
+        assert_eq!(
+            "1.2.3",
+            Transform::StripPrefix("v".to_string()).apply("v1.2.3")
+        );
+        assert_eq!(
+            "1.2.3",
+            Transform::StripPrefix("v".to_string()).apply("1.2.3")
+        );
+        assert_eq!("main", Transform::Lowercase.apply("MAIN"));
+        assert_eq!("MAIN", Transform::Uppercase.apply("main"));
+    }
+
+    #[test]
+    fn idempotent_is_not_transformed() {
+        assert_eq!(
+            "VERGEN_IDEMPOTENT_OUTPUT",
+            Transform::Truncate(7).apply("VERGEN_IDEMPOTENT_OUTPUT")
+        );
+    }
+
+    #[test]
+    fn slugify_works() {
+        assert_eq!("feature-my-branch", slugify("feature/My_Branch"));
+        assert_eq!("a-b", slugify("--a  b--"));
+        assert_eq!("", slugify("///"));
+    }
+
+    #[test]
+    fn render_works() {
+        let lookup = |name: &str| match name {
+            "A" => Some("1.0.0".to_string()),
+            "B" => Some("abcdef".to_string()),
+            _ => None,
+        };
+        assert_eq!(Ok("1.0.0+abcdef".to_string()), render("{A}+{B}", lookup));
+        assert_eq!(Ok("{A} 1.0.0".to_string()), render("{{A}} {A}", lookup));
+        assert_eq!(Err("C".to_string()), render("{A}-{C}", lookup));
+        assert_eq!(Err("{A".to_string()), render("{A", lookup));
+    }
+}
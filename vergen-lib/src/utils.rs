@@ -1,20 +1,29 @@
-use crate::{CargoRustcEnvMap, CargoWarning, VergenKey, constants::VERGEN_IDEMPOTENT_DEFAULT};
+use crate::{
+    CargoRustcEnvMap, CargoWarning, EntryContext, Error, FailurePolicy, VergenKey,
+    constants::VERGEN_IDEMPOTENT_DEFAULT,
+};
+use std::collections::BTreeMap;
+#[cfg(feature = "vcs_info")]
 use std::env;
 
 /// Add a [`VergenKey`] entry as a default string into the [`CargoRustcEnvMap`].
 /// The value is either from an environment variable override or [`crate::constants::VERGEN_IDEMPOTENT_DEFAULT`]
 ///
+/// The override variable is read with [`EntryContext::env_override`], so it follows any
+/// prefix or rename configured on the [`Emitter`](crate::Emitter).
+///
 /// # Example
 /// ```
 /// # use std::collections::BTreeMap;
 /// # use temp_env::with_var;
-/// # use vergen_lib::{add_default_map_entry, CargoRustcEnvMap, CargoWarning, VergenKey};
+/// # use vergen_lib::{add_default_map_entry, CargoRustcEnvMap, CargoWarning, EntryContext, VergenKey};
 /// with_var("VERGEN_BUILD_DATE", Some("my own date"), || {
+///     let context = EntryContext::default();
 ///     let mut map: CargoRustcEnvMap = BTreeMap::new();
 ///     let mut warning: CargoWarning = vec![];
 #[cfg_attr(
     feature = "build",
-    doc = r"    add_default_map_entry(false, VergenKey::BuildDate, &mut map, &mut warning);
+    doc = r"    add_default_map_entry(&context, false, VergenKey::BuildDate, &mut map, &mut warning);
 assert_eq!(1, map.len());
 assert_eq!(1, warning.len());"
 )]
@@ -22,19 +31,73 @@ assert_eq!(1, warning.len());"
 /// ```
 ///
 pub fn add_default_map_entry(
+    context: &EntryContext,
     idempotent: bool,
     key: VergenKey,
     map: &mut CargoRustcEnvMap,
     warnings: &mut CargoWarning,
 ) {
-    if let Ok(value) = env::var(key.name()) {
+    let name = context.env_name(key);
+    if let Some(value) = context.env_override(key) {
         add_map_entry(key, value, map);
-        warnings.push(format!("{} overidden", key.name()));
+        warnings.push(format!("{name} overidden"));
     } else if idempotent {
         add_map_entry(key, VERGEN_IDEMPOTENT_DEFAULT, map);
-        warnings.push(format!("{} set to default", key.name()));
+        context.record_default(key);
+        warnings.push(format!("{name} set to default"));
     } else {
-        warnings.push(format!("Unable to set {}", key.name()));
+        context.record_missing(key);
+        warnings.push(format!("Unable to set {name}"));
+    }
+}
+
+/// Handle a failure to generate a single [`VergenKey`] according to the
+/// [`key_policy`](crate::Emitter::key_policy) configured on the [`Emitter`](crate::Emitter).
+///
+/// With [`FailurePolicy::Default`] the key is added with [`add_default_map_entry`], and with
+/// [`FailurePolicy::Skip`] nothing is added.  Otherwise the error, carrying the key, is returned
+/// so the failure is handled by the policy of the instruction set.
+///
+/// # Errors
+///
+/// The given error is returned unless a [`FailurePolicy::Default`] or [`FailurePolicy::Skip`]
+/// policy is configured for the key.
+///
+/// # Example
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use vergen_lib::{handle_key_failure, CargoRustcEnvMap, CargoWarning, EntryContext, Error, VergenKey};
+/// let context = EntryContext::default();
+/// let mut map: CargoRustcEnvMap = BTreeMap::new();
+/// let mut warning: CargoWarning = vec![];
+#[cfg_attr(
+    feature = "build",
+    doc = r#"let error = Error::time_format("bad format");
+let result = handle_key_failure(&context, VergenKey::BuildDate, error, &mut map, &mut warning);
+assert!(result.is_err());"#
+)]
+/// ```
+///
+pub fn handle_key_failure(
+    context: &EntryContext,
+    key: VergenKey,
+    error: Error,
+    map: &mut CargoRustcEnvMap,
+    warnings: &mut CargoWarning,
+) -> Result<(), Error> {
+    let error = error.with_key(key);
+    match context.policy(key) {
+        Some(FailurePolicy::Default) => {
+            context.record_failure(key, &error);
+            add_default_map_entry(context, true, key, map, warnings);
+            Ok(())
+        }
+        Some(FailurePolicy::Skip) => {
+            context.record_failure(key, &error);
+            context.record_missing(key);
+            Ok(())
+        }
+        Some(FailurePolicy::Fail) | None => Err(error),
     }
 }
 
@@ -59,7 +122,8 @@ where
     let _old = map.insert(key, value.into());
 }
 
-/// Count the number of idempotent entries in a [`CargoRustcEnvMap`]
+/// Count the number of idempotent entries in a [`CargoRustcEnvMap`], or in the map of
+/// custom entries returned by [`Emitter::cargo_rustc_env_map_custom`](crate::Emitter::cargo_rustc_env_map_custom)
 ///
 /// **NOTE** - This is mainly used for testing.
 ///
@@ -79,7 +143,7 @@ where
 /// ```
 ///
 #[must_use]
-pub fn count_idempotent(map: &CargoRustcEnvMap) -> usize {
+pub fn count_idempotent<K>(map: &BTreeMap<K, String>) -> usize {
     map.values()
         .filter(|x| *x == VERGEN_IDEMPOTENT_DEFAULT)
         .count()
@@ -0,0 +1,229 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Sharing of instruction set output between the build scripts of a workspace
+
+use crate::cache::find_git_dir;
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often to check whether another build script has released the lock
+const POLL: Duration = Duration::from_millis(25);
+/// A lock held for longer was left behind by a build script that did not finish
+const STALE: Duration = Duration::from_mins(2);
+/// Give up on the lock, and generate the output without it, after this long
+const TIMEOUT: Duration = Duration::from_mins(10);
+/// Without a build identifier, shared output older than this is not reused
+#[cfg(not(unix))]
+const MAX_AGE: Duration = Duration::from_mins(5);
+
+/// The directory shared by the build scripts of a workspace, the profile directory
+/// under `target/` derived from `$OUT_DIR`, i.e. `target/debug/vergen`
+pub(crate) fn shared_dir() -> Option<PathBuf> {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR")?);
+    let build = out_dir.parent()?.parent()?;
+    if build.file_name()? != "build" {
+        return None;
+    }
+    Some(build.parent()?.join("vergen"))
+}
+
+/// The key identifying the workspace, its checked out commit and the current build
+pub(crate) fn shared_key() -> String {
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_default();
+    let root = workspace_root(&manifest_dir);
+    format!(
+        "root={}\nhead={}\nbuild={}",
+        root.display(),
+        head(&root).unwrap_or_default(),
+        build_id()
+    )
+}
+
+/// Whether shared output stored in the given file may be reused, always, as the key
+/// includes the [`build_id`]
+#[cfg(unix)]
+pub(crate) fn is_fresh(_file: &Path) -> bool {
+    true
+}
+
+/// Whether shared output stored in the given file may be reused, only if it is recent
+/// as there is no [`build_id`]
+#[cfg(not(unix))]
+pub(crate) fn is_fresh(file: &Path) -> bool {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < MAX_AGE)
+}
+
+/// The outermost directory holding a `Cargo.toml` with a `[workspace]` table, or
+/// the package directory if it is not part of a workspace
+fn workspace_root(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .ancestors()
+        .filter(|dir| {
+            fs::read_to_string(dir.join("Cargo.toml"))
+                .is_ok_and(|manifest| manifest.lines().any(|line| line.trim() == "[workspace]"))
+        })
+        .last()
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
+/// The commit checked out in the repository containing the given directory
+fn head(dir: &Path) -> Option<String> {
+    let git_dir = find_git_dir(dir)?;
+    let common_dir = fs::read_to_string(git_dir.join("commondir"))
+        .map_or_else(|_| git_dir.clone(), |common| git_dir.join(common.trim()));
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let Some(reference) = head.trim().strip_prefix("ref: ") else {
+        return Some(head.trim().to_string());
+    };
+    if let Ok(commit) = fs::read_to_string(common_dir.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    fs::read_to_string(common_dir.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            line.strip_suffix(reference)
+                .and_then(|commit| commit.strip_suffix(' '))
+                .map(str::to_string)
+        })
+}
+
+/// The build scripts of one `cargo` invocation are run by the same `cargo` process
+#[cfg(unix)]
+fn build_id() -> String {
+    std::os::unix::process::parent_id().to_string()
+}
+
+/// There is no portable way to identify the `cargo` process elsewhere
+#[cfg(not(unix))]
+fn build_id() -> String {
+    String::new()
+}
+
+/// A lock file next to the shared output, removed when dropped
+#[derive(Debug)]
+pub(crate) struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    /// Wait for any other build script generating the output to finish, then take the
+    /// lock.  Returns `None` if the lock cannot be created or is not released in time.
+    pub(crate) fn acquire(file: &Path) -> Option<Self> {
+        let mut path = file.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+        fs::create_dir_all(path.parent()?).ok()?;
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_file) => return Some(Self { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if Self::is_stale(&path) {
+                        let _res = fs::remove_file(&path);
+                    } else if start.elapsed() > TIMEOUT {
+                        return None;
+                    } else {
+                        thread::sleep(POLL);
+                    }
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn is_stale(path: &Path) -> bool {
+        File::open(path)
+            .and_then(|file| file.metadata())
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > STALE)
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _res = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Lock, head, shared_dir, workspace_root};
+    use serial_test::serial;
+    use std::{
+        env, fs, process,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn lock_waits_for_release() {
+        let dir = env::temp_dir().join(format!("vergen_workspace_lock_{}", process::id()));
+        let file = dir.join("shared");
+        let lock = Lock::acquire(&file).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let waiter = thread::spawn(move || {
+            let start = Instant::now();
+            let lock = Lock::acquire(&file);
+            tx.send(start.elapsed()).unwrap();
+            lock.is_some()
+        });
+        thread::sleep(Duration::from_millis(200));
+        drop(lock);
+        assert!(rx.recv().unwrap() >= Duration::from_millis(150));
+        assert!(waiter.join().unwrap());
+        let _res = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[serial]
+    fn shared_dir_works() {
+        let out_dir = env::temp_dir()
+            .join("target")
+            .join("debug")
+            .join("build")
+            .join("crate-0123")
+            .join("out");
+        temp_env::with_var("OUT_DIR", Some(&out_dir), || {
+            let expected = env::temp_dir().join("target").join("debug").join("vergen");
+            assert_eq!(Some(expected), shared_dir());
+        });
+        temp_env::with_var("OUT_DIR", Some(env::temp_dir()), || {
+            assert!(shared_dir().is_none());
+        });
+    }
+
+    #[test]
+    fn workspace_root_works() {
+        let manifest_dir = env::current_dir().unwrap();
+        let root = workspace_root(&manifest_dir);
+        assert!(manifest_dir.starts_with(&root));
+        assert!(fs::read_to_string(root.join("Cargo.toml")).is_ok());
+        if root.join(".git").exists() {
+            assert!(head(&root).is_some_and(|commit| commit.len() >= 40));
+        }
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Selection of the time zone date and timestamp output is rendered in
+
+use crate::Error;
+use jiff::Timestamp;
+use std::{env, sync::Arc};
+use time::{OffsetDateTime, UtcOffset};
+
+/// The environment variable overriding the configured [`TimeZone`]
+pub const VERGEN_TZ: &str = "VERGEN_TZ";
+
+/// The time zone date and timestamp output is rendered in
+///
+/// The `VERGEN_TZ` environment variable overrides the configured time zone.  It accepts
+/// `UTC`, `local`, a fixed offset such as `+05:30` or `-0800`, or an IANA time zone name
+/// such as `Europe/Berlin`.
+///
+/// # Example
+///
+/// ```
+/// # use vergen_lib::TimeZone;
+/// # use time::UtcOffset;
+/// #
+/// // A fixed offset
+/// let zone = TimeZone::Offset(UtcOffset::from_hms(5, 30, 0).unwrap());
+/// // The offset of the named zone, including daylight saving time, at the time being rendered
+/// let zone = TimeZone::Named("Europe/Berlin".into());
+/// ```
+// The owned name is pointer sized, boxing it again would not bring it within 3x of `UtcOffset`
+#[cfg_attr(nightly, allow(variant_size_differences))]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TimeZone {
+    /// Coordinated Universal Time
+    Utc,
+    /// The system time zone of the machine running the build, from the `TZ` environment
+    /// variable or `/etc/localtime` on Unix and the registry on Windows
+    Local,
+    /// A fixed offset from UTC
+    Offset(UtcOffset),
+    /// An IANA time zone name, i.e. `America/New_York`, resolved with the system time
+    /// zone database in `/usr/share/zoneinfo`, or the directory named by `TZDIR`.  Where
+    /// there is no system database, i.e. on Windows, a copy bundled at compile time is used.
+    Named(Arc<str>),
+}
+
+impl TimeZone {
+    /// Convert the given date and time to this time zone
+    ///
+    /// # Errors
+    ///
+    /// * The system time zone cannot be determined.
+    /// * The named time zone is not found in the time zone database.
+    ///
+    pub fn convert(&self, ts: OffsetDateTime) -> Result<OffsetDateTime, Error> {
+        let offset = match self {
+            TimeZone::Utc => UtcOffset::UTC,
+            TimeZone::Local => {
+                let zone = jiff::tz::TimeZone::try_system().map_err(Error::invalid_time_zone)?;
+                offset_at(&zone, ts.unix_timestamp())?
+            }
+            TimeZone::Offset(offset) => *offset,
+            TimeZone::Named(name) => named_offset(name, ts.unix_timestamp())?,
+        };
+        Ok(ts.to_offset(offset))
+    }
+
+    /// Parse `UTC`, `local`, a fixed offset or an IANA time zone name, as accepted by
+    /// the `VERGEN_TZ` environment variable
+    pub(crate) fn parse(spec: &str) -> Self {
+        Self::parse_fixed(spec).unwrap_or_else(|| TimeZone::Named(spec.into()))
+    }
+
+    /// Parse `UTC`, `local` or a fixed offset
+    fn parse_fixed(spec: &str) -> Option<Self> {
+        if spec.eq_ignore_ascii_case("utc") || spec == "Z" {
+            Some(TimeZone::Utc)
+        } else if spec.eq_ignore_ascii_case("local") {
+            Some(TimeZone::Local)
+        } else {
+            parse_offset(spec).map(TimeZone::Offset)
+        }
+    }
+}
+
+/// Convert the given date and time to the time zone named by the `VERGEN_TZ` environment
+/// variable if it is set, otherwise to the given time zone, if any
+///
+/// # Errors
+///
+/// * `VERGEN_TZ` is not `UTC`, `local`, a valid offset or a known time zone name.
+/// * The time zone cannot be determined, see [`TimeZone::convert`].
+///
+/// # Example
+///
+/// ```
+/// # use vergen_lib::{in_time_zone, Error, TimeZone};
+/// # use time::{OffsetDateTime, UtcOffset};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # temp_env::with_var_unset("VERGEN_TZ", || -> Result<(), Error> {
+/// let offset = UtcOffset::from_hms(-8, 0, 0).unwrap();
+/// let ts = in_time_zone(OffsetDateTime::UNIX_EPOCH, Some(&TimeZone::Offset(offset)))?;
+/// assert_eq!(offset, ts.offset());
+/// #     Ok(())
+/// # })
+/// # }
+/// ```
+pub fn in_time_zone(ts: OffsetDateTime, zone: Option<&TimeZone>) -> Result<OffsetDateTime, Error> {
+    match env::var(VERGEN_TZ) {
+        Ok(spec) if !spec.trim().is_empty() => {
+            let spec = spec.trim();
+            match TimeZone::parse_fixed(spec) {
+                Some(zone) => zone.convert(ts),
+                None => Ok(ts.to_offset(named_offset(spec, ts.unix_timestamp())?)),
+            }
+        }
+        _ => zone.map_or(Ok(ts), |zone| zone.convert(ts)),
+    }
+}
+
+/// Parse an ISO 8601 offset, `[+-]hh[:mm[:ss]]` or `[+-]hhmm`
+fn parse_offset(spec: &str) -> Option<UtcOffset> {
+    let (sign, rest) = match spec.as_bytes().first()? {
+        b'+' => (1, &spec[1..]),
+        b'-' => (-1, &spec[1..]),
+        _ => return None,
+    };
+    let digits = rest.replace(':', "");
+    if !matches!(digits.len(), 2 | 4 | 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |idx: usize| digits.get(idx..idx + 2).map_or(Ok(0), str::parse::<i8>);
+    UtcOffset::from_hms(
+        sign * field(0).ok()?,
+        sign * field(2).ok()?,
+        sign * field(4).ok()?,
+    )
+    .ok()
+}
+
+/// The offset of the time zone at the given Unix time
+fn offset_at(zone: &jiff::tz::TimeZone, at: i64) -> Result<UtcOffset, Error> {
+    let at = Timestamp::from_second(at).map_err(Error::invalid_time_zone)?;
+    UtcOffset::from_whole_seconds(zone.to_offset(at).seconds()).map_err(Error::invalid_time_zone)
+}
+
+/// The offset of the named time zone at the given Unix time
+fn named_offset(name: &str, at: i64) -> Result<UtcOffset, Error> {
+    let zone = jiff::tz::TimeZone::get(name).map_err(Error::invalid_time_zone)?;
+    offset_at(&zone, at)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimeZone, in_time_zone, parse_offset};
+    use anyhow::Result;
+    use serial_test::serial;
+    use std::ffi::OsStr;
+    use time::{OffsetDateTime, UtcOffset};
+
+    /// 2022-12-23T15:29:20Z
+    const WINTER: i64 = 1_671_809_360;
+    /// 2023-07-01T12:00:00Z
+    const SUMMER: i64 = 1_688_212_800;
+
+    #[test]
+    fn parse_offset_works() {
+        let offset = |h, m, s| UtcOffset::from_hms(h, m, s).ok();
+        assert_eq!(offset(5, 30, 0), parse_offset("+05:30"));
+        assert_eq!(offset(-8, 0, 0), parse_offset("-0800"));
+        assert_eq!(offset(1, 0, 0), parse_offset("+01"));
+        assert_eq!(offset(-1, -2, -3), parse_offset("-01:02:03"));
+        assert_eq!(None, parse_offset("05:30"));
+        assert_eq!(None, parse_offset("+5"));
+        assert_eq!(None, parse_offset("Europe/Berlin"));
+    }
+
+    #[test]
+    fn named_time_zone_works() -> Result<()> {
+        let hours = |h| UtcOffset::from_hms(h, 0, 0).unwrap();
+        let berlin = TimeZone::Named("Europe/Berlin".into());
+        let winter = OffsetDateTime::from_unix_timestamp(WINTER)?;
+        let summer = OffsetDateTime::from_unix_timestamp(SUMMER)?;
+        assert_eq!(hours(1), berlin.convert(winter)?.offset());
+        assert_eq!(hours(2), berlin.convert(summer)?.offset());
+        assert_eq!(summer, berlin.convert(summer)?);
+        let new_york = TimeZone::Named("America/New_York".into());
+        assert_eq!(hours(-5), new_york.convert(winter)?.offset());
+        assert_eq!(hours(-4), new_york.convert(summer)?.offset());
+        assert!(
+            TimeZone::Named("Mars/Olympus".into())
+                .convert(summer)
+                .is_err()
+        );
+        assert!(TimeZone::Named("../Berlin".into()).convert(summer).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn in_time_zone_works() -> Result<()> {
+        let ts = OffsetDateTime::from_unix_timestamp(SUMMER)?;
+        let hours = |h| UtcOffset::from_hms(h, 0, 0).unwrap();
+        temp_env::with_var_unset("VERGEN_TZ", || -> Result<()> {
+            let berlin = Some(TimeZone::Named("Europe/Berlin".into()));
+            assert_eq!(hours(2), in_time_zone(ts, berlin.as_ref())?.offset());
+            assert_eq!(ts, in_time_zone(ts, None)?);
+            Ok(())
+        })?;
+        for (spec, offset) in [("Europe/Berlin", 2), ("-08:00", -8), ("UTC", 0)] {
+            temp_env::with_var("VERGEN_TZ", Some(OsStr::new(spec)), || -> Result<()> {
+                let zone = Some(TimeZone::Offset(hours(9)));
+                assert_eq!(hours(offset), in_time_zone(ts, zone.as_ref())?.offset());
+                Ok(())
+            })?;
+        }
+        temp_env::with_var("VERGEN_TZ", Some("Mars/Olympus"), || {
+            assert!(in_time_zone(ts, None).is_err());
+        });
+        Ok(())
+    }
+}
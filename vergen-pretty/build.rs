@@ -7,7 +7,7 @@ use {
     std::collections::BTreeMap,
     vergen_gix::{
         AddCustomEntries, Build, Cargo, CargoRerunIfChanged, CargoWarning, DefaultConfig, Emitter,
-        Gix, Rustc, Sysinfo,
+        Error, Gix, Rustc, Sysinfo,
     },
 };
 
@@ -48,7 +48,7 @@ impl AddCustomEntries<&str, &str> for Custom {
         cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         cargo_rustc_env_map.insert("vergen-cl", "custom_instruction");
         cargo_warning.push("custom instruction generated".to_string());
         Ok(())
@@ -60,7 +60,7 @@ impl AddCustomEntries<&str, &str> for Custom {
         _cargo_rustc_env_map: &mut BTreeMap<&str, &str>,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         _cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         Ok(())
     }
 }
@@ -73,20 +73,22 @@ fn emit() -> Result<()> {
     let gix = Gix::all_git();
     let rustc = Rustc::all_rustc();
     let si = Sysinfo::all_sysinfo();
-    Emitter::default()
+    let _ = Emitter::default()
         .add_instructions(&build)?
         .add_instructions(&cargo)?
         .add_instructions(&gix)?
         .add_instructions(&rustc)?
         .add_instructions(&si)?
         .add_custom_instructions(&Custom::default())?
-        .emit()
+        .emit()?;
+    Ok(())
 }
 
 #[cfg(all(not(feature = "__vergen_test"), feature = "__vergen_empty_test"))]
 fn emit() -> Result<()> {
     println!("cargo:warning=VERGEN EMPTY TEST ENABLED!");
-    Emitter::default().emit()
+    Emitter::default().emit()?;
+    Ok(())
 }
 
 #[rustversion::nightly]
@@ -0,0 +1,236 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::borrow::Cow;
+
+const ESCAPED_MARKER: &[u8] = b"vergen-escaped:";
+const BASE64_MARKER: &[u8] = b"vergen-base64:";
+
+/// Decode a value emitted with a lossless `vergen` value encoding
+///
+/// Values emitted with `ValueEncoding::Escaped` or `ValueEncoding::Base64` carry
+/// an encoding marker and are decoded, i.e. restoring the newlines of a multi-line
+/// commit message.  Any other value, or a value that fails to decode, is returned as is.
+///
+/// # Example
+/// ```
+/// # use vergen_pretty::decode;
+/// #
+/// assert_eq!("subject\n\nbody", decode(r"vergen-escaped:subject\n\nbody"));
+/// assert_eq!("a\nb", decode("vergen-base64:YQpi"));
+/// assert_eq!("plain", decode("plain"));
+/// ```
+#[must_use]
+pub fn decode(value: &str) -> Cow<'_, str> {
+    if !value.as_bytes().starts_with(ESCAPED_MARKER) && !value.as_bytes().starts_with(BASE64_MARKER)
+    {
+        return Cow::Borrowed(value);
+    }
+    let mut buf = vec![0; value.len()];
+    match decode_into(value.as_bytes(), &mut buf) {
+        Some(len) => {
+            buf.truncate(len);
+            String::from_utf8(buf).map_or(Cow::Borrowed(value), Cow::Owned)
+        }
+        None => Cow::Borrowed(value),
+    }
+}
+
+/// Decode a compile time value into a fixed size buffer, panicking, and so failing
+/// the build, if it cannot be decoded.  Used by [`vergen_decode!`](crate::vergen_decode).
+#[doc(hidden)]
+#[must_use]
+pub const fn decode_const<const N: usize>(value: &str) -> ([u8; N], usize) {
+    let mut buf = [0; N];
+    match decode_into(value.as_bytes(), &mut buf) {
+        Some(len) => (buf, len),
+        None => panic!("invalid vergen encoded value"),
+    }
+}
+
+/// View the decoded part of a buffer as a `str`.  Used by [`vergen_decode!`](crate::vergen_decode).
+#[doc(hidden)]
+#[must_use]
+pub const fn decoded_str(buf: &[u8], len: usize) -> &str {
+    let (decoded, _) = buf.split_at(len);
+    match str::from_utf8(decoded) {
+        Ok(decoded) => decoded,
+        Err(_) => panic!("vergen encoded value is not UTF-8"),
+    }
+}
+
+/// Decode the value into the buffer, which must be at least as long as the value,
+/// returning the decoded length
+const fn decode_into(value: &[u8], buf: &mut [u8]) -> Option<usize> {
+    if starts_with(value, ESCAPED_MARKER) {
+        unescape(value, ESCAPED_MARKER.len(), buf)
+    } else if starts_with(value, BASE64_MARKER) {
+        unbase64(value, BASE64_MARKER.len(), buf)
+    } else {
+        let mut i = 0;
+        while i < value.len() {
+            buf[i] = value[i];
+            i += 1;
+        }
+        Some(value.len())
+    }
+}
+
+const fn starts_with(value: &[u8], marker: &[u8]) -> bool {
+    if value.len() < marker.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < marker.len() {
+        if value[i] != marker[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+pub(crate) const fn unescape(value: &[u8], start: usize, buf: &mut [u8]) -> Option<usize> {
+    let mut i = start;
+    let mut len = 0;
+    while i < value.len() {
+        let byte = if value[i] == b'\\' {
+            if i + 1 >= value.len() {
+                return None;
+            }
+            i += 1;
+            match value[i] {
+                b'\\' => b'\\',
+                b'n' => b'\n',
+                b'r' => b'\r',
+                b't' => b'\t',
+                b'x' if i + 2 < value.len() => match (hex(value[i + 1]), hex(value[i + 2])) {
+                    (Some(hi), Some(lo)) => {
+                        i += 2;
+                        (hi << 4) | lo
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        } else {
+            value[i]
+        };
+        buf[len] = byte;
+        len += 1;
+        i += 1;
+    }
+    Some(len)
+}
+
+const fn hex(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn unbase64(value: &[u8], start: usize, buf: &mut [u8]) -> Option<usize> {
+    let mut i = start;
+    let mut len = 0;
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    while i < value.len() && value[i] != b'=' {
+        let sextet = match value[i] {
+            b'A'..=b'Z' => value[i] - b'A',
+            b'a'..=b'z' => value[i] - b'a' + 26,
+            b'0'..=b'9' => value[i] - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | sextet as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            buf[len] = (acc >> bits) as u8;
+            acc &= (1 << bits) - 1;
+            len += 1;
+        }
+        i += 1;
+    }
+    Some(len)
+}
+
+/// Embed a `vergen` environment variable, decoding a lossless value encoding at compile time
+///
+/// This is the compile time counterpart of [`decode`](crate::decode) and expands to
+/// a `&'static str` that can be used to initialize a `const` or `static`.  A value
+/// without an encoding marker is embedded as is.  The build fails if the variable
+/// is not set or the value cannot be decoded.
+///
+/// # Example
+/// ```
+/// # use vergen_pretty::vergen_decode;
+/// #
+/// const PKG_NAME: &str = vergen_decode!("CARGO_PKG_NAME");
+/// assert_eq!("vergen-pretty", PKG_NAME);
+/// ```
+#[macro_export]
+macro_rules! vergen_decode {
+    ($name:literal) => {{
+        const ENCODED: &str = env!($name);
+        const DECODED: ([u8; ENCODED.len()], usize) =
+            $crate::decode_const::<{ ENCODED.len() }>(ENCODED);
+        const VALUE: &str = $crate::decoded_str(&DECODED.0, DECODED.1);
+        VALUE
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_const, decoded_str};
+
+    #[test]
+    fn decode_escaped_works() {
+        assert_eq!("subject\n\nbody", decode(r"vergen-escaped:subject\n\nbody"));
+        assert_eq!("a\\b\r\t\x1b", decode(r"vergen-escaped:a\\b\r\t\x1B"));
+        assert_eq!(
+            "vergen-escaped:x",
+            decode("vergen-escaped:vergen-escaped:x")
+        );
+    }
+
+    #[test]
+    fn decode_base64_works() {
+        assert_eq!("", decode("vergen-base64:"));
+        assert_eq!("f", decode("vergen-base64:Zg=="));
+        assert_eq!("fo", decode("vergen-base64:Zm8="));
+        assert_eq!("foobar", decode("vergen-base64:Zm9vYmFy"));
+    }
+
+    #[test]
+    fn decode_invalid_is_unchanged() {
+        assert_eq!("plain", decode("plain"));
+        assert_eq!(r"vergen-escaped:bad\q", decode(r"vergen-escaped:bad\q"));
+        assert_eq!(r"vergen-escaped:bad\", decode(r"vergen-escaped:bad\"));
+        assert_eq!("vergen-base64:!!", decode("vergen-base64:!!"));
+    }
+
+    #[test]
+    fn decode_const_works() {
+        const ENCODED: &str = r"vergen-escaped:subject\n\nbody";
+        const DECODED: ([u8; ENCODED.len()], usize) = decode_const::<{ ENCODED.len() }>(ENCODED);
+        const VALUE: &str = decoded_str(&DECODED.0, DECODED.1);
+        assert_eq!("subject\n\nbody", VALUE);
+    }
+
+    #[test]
+    fn vergen_decode_works() {
+        const NAME: &str = vergen_decode!("CARGO_PKG_NAME");
+        assert_eq!("vergen-pretty", NAME);
+    }
+}
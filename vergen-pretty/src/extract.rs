@@ -0,0 +1,414 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use crate::decode::unescape;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::Path,
+};
+
+/// The link section the `vergen` values are embedded in by default
+pub const LINK_SECTION: &str = ".vergen_info";
+
+const HEADER: &[u8] = b"vergen-info 1";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const SHT_NOBITS: usize = 8;
+
+/// An error extracting the embedded `vergen` values from a binary
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The binary could not be read
+    Io(io::Error),
+    /// The binary is not an ELF file
+    NotElf,
+    /// The ELF file is truncated or its section headers are invalid
+    Malformed,
+    /// The ELF file has no section with the given name
+    MissingSection(String),
+    /// The section does not hold `vergen` values
+    InvalidPayload,
+}
+
+impl Display for ExtractError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::Io(e) => write!(f, "unable to read the binary: {e}"),
+            ExtractError::NotElf => f.write_str("the binary is not an ELF file"),
+            ExtractError::Malformed => f.write_str("the ELF file is malformed"),
+            ExtractError::MissingSection(name) => {
+                write!(f, "the ELF file has no '{name}' section")
+            }
+            ExtractError::InvalidPayload => f.write_str("the section does not hold vergen values"),
+        }
+    }
+}
+
+impl Error for ExtractError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExtractError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ExtractError {
+    fn from(e: io::Error) -> Self {
+        ExtractError::Io(e)
+    }
+}
+
+/// Extract the `vergen` values embedded with `Emitter::link_section` from the
+/// ELF binary at the given path, without running it
+///
+/// # Errors
+///
+/// An [`ExtractError`] if the file cannot be read, is not an ELF file, or does not
+/// hold the values in the [`LINK_SECTION`].
+///
+/// # Example
+/// ```no_run
+/// # use anyhow::Result;
+/// # use vergen_pretty::extract_file;
+/// #
+/// # fn main() -> Result<()> {
+/// let values = extract_file("target/release/my-binary")?;
+/// if let Some(sha) = values.get("VERGEN_GIT_SHA") {
+///     println!("built from {sha}");
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+pub fn extract_file<P>(path: P) -> Result<BTreeMap<String, String>, ExtractError>
+where
+    P: AsRef<Path>,
+{
+    extract(&fs::read(path)?)
+}
+
+/// Extract the `vergen` values embedded in the [`LINK_SECTION`] from the bytes of
+/// an ELF binary
+///
+/// # Errors
+///
+/// An [`ExtractError`] if the bytes are not an ELF file or do not hold the values
+/// in the [`LINK_SECTION`].
+pub fn extract(elf: &[u8]) -> Result<BTreeMap<String, String>, ExtractError> {
+    extract_section(elf, LINK_SECTION)
+}
+
+/// Extract the `vergen` values embedded in the given link section, i.e. one set with
+/// `Emitter::link_section_name`, from the bytes of an ELF binary
+///
+/// # Errors
+///
+/// An [`ExtractError`] if the bytes are not an ELF file or do not hold the values
+/// in the given section.
+pub fn extract_section(
+    elf: &[u8],
+    section: &str,
+) -> Result<BTreeMap<String, String>, ExtractError> {
+    let elf = Elf::parse(elf)?;
+    let data = elf
+        .section(section)?
+        .ok_or_else(|| ExtractError::MissingSection(section.to_string()))?;
+    parse_payload(data)
+}
+
+/// Parse the `NAME=value` lines following the header, up to the end of the section or
+/// any trailing padding.  A header line repeated by another embedded payload is skipped.
+fn parse_payload(data: &[u8]) -> Result<BTreeMap<String, String>, ExtractError> {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    let mut lines = data[..end].split(|b| *b == b'\n');
+    if lines.next() != Some(HEADER) {
+        return Err(ExtractError::InvalidPayload);
+    }
+    let mut values = BTreeMap::new();
+    for line in lines.filter(|line| !line.is_empty() && *line != HEADER) {
+        let split = line
+            .iter()
+            .position(|b| *b == b'=')
+            .ok_or(ExtractError::InvalidPayload)?;
+        let (name, value) = (&line[..split], &line[split + 1..]);
+        let mut buf = vec![0; value.len()];
+        let len = unescape(value, 0, &mut buf).ok_or(ExtractError::InvalidPayload)?;
+        buf.truncate(len);
+        let name = String::from_utf8(name.to_vec()).map_err(|_| ExtractError::InvalidPayload)?;
+        let value = String::from_utf8(buf).map_err(|_| ExtractError::InvalidPayload)?;
+        let _old = values.insert(name, value);
+    }
+    Ok(values)
+}
+
+/// A minimal reader for the section headers of a 32 or 64-bit, little or big endian
+/// ELF file
+struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    is_le: bool,
+}
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, ExtractError> {
+        if data.len() < 16 || !data.starts_with(ELF_MAGIC) {
+            return Err(ExtractError::NotElf);
+        }
+        let is_64 = match data[4] {
+            1 => false,
+            2 => true,
+            _ => return Err(ExtractError::NotElf),
+        };
+        let is_le = match data[5] {
+            1 => true,
+            2 => false,
+            _ => return Err(ExtractError::NotElf),
+        };
+        Ok(Self { data, is_64, is_le })
+    }
+
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N], ExtractError> {
+        offset
+            .checked_add(N)
+            .and_then(|end| self.data.get(offset..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ExtractError::Malformed)
+    }
+
+    fn u16(&self, offset: usize) -> Result<usize, ExtractError> {
+        let bytes = self.bytes(offset)?;
+        let value = if self.is_le {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        };
+        Ok(usize::from(value))
+    }
+
+    fn u32(&self, offset: usize) -> Result<usize, ExtractError> {
+        let bytes = self.bytes(offset)?;
+        let value = if self.is_le {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        };
+        usize::try_from(value).map_err(|_| ExtractError::Malformed)
+    }
+
+    /// Read an address or offset sized for the ELF class
+    fn word(&self, offset: usize) -> Result<usize, ExtractError> {
+        if !self.is_64 {
+            return self.u32(offset);
+        }
+        let bytes = self.bytes(offset)?;
+        let value = if self.is_le {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        };
+        usize::try_from(value).map_err(|_| ExtractError::Malformed)
+    }
+
+    /// The offset of the given section header, which lies within the file
+    fn header(&self, index: usize) -> Result<usize, ExtractError> {
+        let (shoff, shentsize) = if self.is_64 {
+            (self.word(0x28)?, self.u16(0x3A)?)
+        } else {
+            (self.word(0x20)?, self.u16(0x2E)?)
+        };
+        index
+            .checked_mul(shentsize)
+            .and_then(|offset| offset.checked_add(shoff))
+            .filter(|offset| *offset < self.data.len())
+            .ok_or(ExtractError::Malformed)
+    }
+
+    /// The data of the given section, empty if it occupies no space in the file
+    fn section_data(&self, index: usize) -> Result<&'a [u8], ExtractError> {
+        let header = self.header(index)?;
+        let (offset, size) = if self.is_64 {
+            (self.word(header + 0x18)?, self.word(header + 0x20)?)
+        } else {
+            (self.word(header + 0x10)?, self.word(header + 0x14)?)
+        };
+        if self.u32(header + 4)? == SHT_NOBITS {
+            return Ok(&[]);
+        }
+        offset
+            .checked_add(size)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or(ExtractError::Malformed)
+    }
+
+    /// Find the data of the section with the given name
+    fn section(&self, name: &str) -> Result<Option<&'a [u8]>, ExtractError> {
+        let (shnum, shstrndx) = if self.is_64 {
+            (self.u16(0x3C)?, self.u16(0x3E)?)
+        } else {
+            (self.u16(0x30)?, self.u16(0x32)?)
+        };
+        if self.word(if self.is_64 { 0x28 } else { 0x20 })? == 0 {
+            return Ok(None);
+        }
+        // Files with many sections store the count and string table index in the
+        // first section header
+        let shnum = if shnum == 0 {
+            let header = self.header(0)?;
+            self.word(header + if self.is_64 { 0x20 } else { 0x14 })?
+        } else {
+            shnum
+        };
+        let shstrndx = if shstrndx == 0xFFFF {
+            self.u32(self.header(0)? + if self.is_64 { 0x28 } else { 0x18 })?
+        } else {
+            shstrndx
+        };
+        let names = self.section_data(shstrndx)?;
+        for index in 0..shnum {
+            let name_offset = self.u32(self.header(index)?)?;
+            let section_name = names
+                .get(name_offset..)
+                .and_then(|rest| rest.split(|b| *b == 0).next())
+                .ok_or(ExtractError::Malformed)?;
+            if section_name == name.as_bytes() {
+                return self.section_data(index).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExtractError, LINK_SECTION, extract, extract_section, parse_payload};
+    use anyhow::Result;
+
+    /// Build an ELF file with the given sections, plus the null section and the
+    /// section name string table
+    fn elf(is_64: bool, is_le: bool, sections: &[(&str, &[u8])]) -> Vec<u8> {
+        let int = |value: usize, size: usize| -> Vec<u8> {
+            let bytes = (value as u64).to_le_bytes()[..size].to_vec();
+            if is_le {
+                bytes
+            } else {
+                bytes.into_iter().rev().collect()
+            }
+        };
+        let word = if is_64 { 8 } else { 4 };
+        let ehsize = if is_64 { 64 } else { 52 };
+        let shentsize = if is_64 { 64 } else { 40 };
+
+        let mut names = vec![0];
+        let mut data = vec![];
+        let mut headers = vec![(0, 0, 0, 0)];
+        for (name, contents) in sections {
+            headers.push((names.len(), 1, ehsize + data.len(), contents.len()));
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+            data.extend_from_slice(contents);
+        }
+        headers.push((names.len(), 3, ehsize + data.len(), 0));
+        names.extend_from_slice(b".shstrtab\0");
+        let last = headers.len() - 1;
+        headers[last].3 = names.len();
+        data.extend_from_slice(&names);
+
+        let mut elf = b"\x7fELF".to_vec();
+        elf.extend_from_slice(&[if is_64 { 2 } else { 1 }, if is_le { 1 } else { 2 }, 1]);
+        elf.resize(16, 0);
+        elf.extend(int(2, 2));
+        elf.extend(int(62, 2));
+        elf.extend(int(1, 4));
+        elf.extend(int(0, word));
+        elf.extend(int(0, word));
+        elf.extend(int(ehsize + data.len(), word));
+        elf.extend(int(0, 4));
+        elf.extend(int(ehsize, 2));
+        elf.extend(int(0, 2));
+        elf.extend(int(0, 2));
+        elf.extend(int(shentsize, 2));
+        elf.extend(int(headers.len(), 2));
+        elf.extend(int(last, 2));
+        assert_eq!(ehsize, elf.len());
+        elf.extend(data);
+        for (name, kind, offset, size) in headers {
+            elf.extend(int(name, 4));
+            elf.extend(int(kind, 4));
+            elf.extend(int(0, word));
+            elf.extend(int(0, word));
+            elf.extend(int(offset, word));
+            elf.extend(int(size, word));
+            elf.resize(elf.len() + shentsize - 8 - 4 * word, 0);
+        }
+        elf
+    }
+
+    const PAYLOAD: &[u8] = b"vergen-info 1\nVERGEN_GIT_SHA=abc\nMESSAGE=multi\\nline\n\0\0";
+
+    #[test]
+    fn extract_works() -> Result<()> {
+        for (is_64, is_le) in [(true, true), (true, false), (false, true), (false, false)] {
+            let elf = elf(is_64, is_le, &[(".text", b"code"), (LINK_SECTION, PAYLOAD)]);
+            let values = extract(&elf)?;
+            assert_eq!(2, values.len());
+            assert_eq!(
+                Some("abc"),
+                values.get("VERGEN_GIT_SHA").map(String::as_str)
+            );
+            assert_eq!(
+                Some("multi\nline"),
+                values.get("MESSAGE").map(String::as_str)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn extract_named_section_works() -> Result<()> {
+        let elf = elf(true, true, &[(".my_info", PAYLOAD)]);
+        assert_eq!(2, extract_section(&elf, ".my_info")?.len());
+        assert!(matches!(
+            extract(&elf),
+            Err(ExtractError::MissingSection(name)) if name == LINK_SECTION
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn extract_invalid_fails() {
+        assert!(matches!(
+            extract(b"not an elf file"),
+            Err(ExtractError::NotElf)
+        ));
+        let elf = elf(true, true, &[(LINK_SECTION, b"something else")]);
+        assert!(matches!(extract(&elf), Err(ExtractError::InvalidPayload)));
+        let truncated = self::elf(false, true, &[(LINK_SECTION, PAYLOAD)]);
+        assert!(matches!(
+            extract(&truncated[..truncated.len() - 40]),
+            Err(ExtractError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn parse_payload_works() -> Result<()> {
+        let values = parse_payload(b"vergen-info 1\nA=1\nvergen-info 1\nB=\\x41\n")?;
+        assert_eq!(Some("1"), values.get("A").map(String::as_str));
+        assert_eq!(Some("A"), values.get("B").map(String::as_str));
+        assert!(matches!(
+            parse_payload(b"vergen-info 1\nno separator\n"),
+            Err(ExtractError::InvalidPayload)
+        ));
+        assert!(matches!(
+            parse_payload(b"vergen-info 1\nA=bad\\q\n"),
+            Err(ExtractError::InvalidPayload)
+        ));
+        Ok(())
+    }
+}
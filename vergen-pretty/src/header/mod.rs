@@ -10,12 +10,14 @@
 
 use crate::{Prefix, Pretty, Suffix};
 
-use anyhow::Result;
 use bon::Builder;
 use console::Style;
 #[cfg(feature = "color")]
 use rand::RngExt;
-use std::{collections::BTreeMap, io::Write};
+use std::{
+    collections::BTreeMap,
+    io::{Result, Write},
+};
 
 #[cfg(feature = "color")]
 fn from_u8(val: u8) -> Style {
@@ -97,7 +99,7 @@ pub struct Config {
 ///
 /// # Errors
 ///
-/// The errors are generally passed up from [`Pretty`] builder.
+/// A [`std::io::Error`] is returned if writing to the given writer fails.
 ///
 pub fn header<T>(config: &Config, writer: Option<&mut T>) -> Result<()>
 where
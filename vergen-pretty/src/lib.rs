@@ -50,6 +50,9 @@
 //!
 //! See the [`Pretty`] documentation for more examples
 //!
+//! The values embedded in a binary with `vergen`'s `Emitter::link_section` can be read
+//! back from the compiled ELF file, without running it, with [`extract_file`].
+//!
 #![cfg_attr(
     feature = "header",
     doc = r"If you enable the header feature, you can also use the [`header()`] function
@@ -321,11 +324,23 @@ assert_ne!(buf.len(), 0);
 )]
 #![cfg_attr(all(docsrs, nightly), feature(doc_cfg))]
 
+mod decode;
+mod extract;
 #[cfg(feature = "header")]
 mod header;
 mod pretty;
 mod utils;
 
+pub use self::decode::decode;
+#[doc(hidden)]
+pub use self::decode::decode_const;
+#[doc(hidden)]
+pub use self::decode::decoded_str;
+pub use self::extract::ExtractError;
+pub use self::extract::LINK_SECTION;
+pub use self::extract::extract;
+pub use self::extract::extract_file;
+pub use self::extract::extract_section;
 #[cfg(feature = "header")]
 pub use self::header::Config;
 #[cfg(feature = "header")]
@@ -348,12 +363,12 @@ pub use tracing::Level;
 
 #[cfg(all(feature = "header", not(feature = "color")))]
 use rand as _;
-#[cfg(test)]
-use regex as _;
 #[cfg(all(test, not(feature = "serde")))]
 use serde_json as _;
 #[cfg(all(test, not(feature = "trace")))]
 use tracing_subscriber as _;
+#[cfg(test)]
+use {anyhow as _, regex as _};
 
 /// Used to initialize `env` in [`Pretty`](self::Pretty)
 ///
@@ -7,9 +7,11 @@
 // modified, or distributed except according to those terms.
 
 use crate::{Prefix, Suffix, pretty::Pretty};
-use anyhow::Result;
 use console::Style;
-use std::{io::Write, sync::LazyLock};
+use std::{
+    io::{Result, Write},
+    sync::LazyLock,
+};
 
 pub(crate) static BOLD_BLUE: LazyLock<Style> = LazyLock::new(|| Style::new().bold().blue());
 pub(crate) static BOLD_GREEN: LazyLock<Style> = LazyLock::new(|| Style::new().bold().green());
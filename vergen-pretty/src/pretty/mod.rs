@@ -8,11 +8,13 @@
 
 use self::{prefix::Prefix, suffix::Suffix};
 use crate::utils::{has_value, split_key, split_kv};
-use anyhow::Result;
 use bon::Builder;
 #[cfg(feature = "color")]
 use console::Style;
-use std::{collections::BTreeMap, io::Write};
+use std::{
+    collections::BTreeMap,
+    io::{Result, Write},
+};
 #[cfg(feature = "trace")]
 use tracing::Level;
 
@@ -6,13 +6,12 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use anyhow::Result;
 use bon::Builder;
 #[cfg(feature = "color")]
 use console::Style;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::io::{Result, Write};
 #[cfg(feature = "trace")]
 use tracing::Level;
 
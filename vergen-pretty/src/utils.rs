@@ -6,6 +6,7 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use crate::decode;
 use convert_case::{Case, Casing};
 
 #[allow(clippy::ref_option_ref)]
@@ -26,9 +27,9 @@ pub(crate) fn split_key(tuple: (&str, &str)) -> Option<(Vec<String>, String)> {
     let key = key.to_ascii_lowercase();
     if key.starts_with("vergen") {
         let kv_vec: Vec<String> = key.split('_').filter_map(not_vergen).collect();
-        Some((kv_vec, value.to_string()))
+        Some((kv_vec, decode(value).into_owned()))
     } else {
-        Some((vec![key], value.to_string()))
+        Some((vec![key], decode(value).into_owned()))
     }
 }
 
@@ -7,20 +7,19 @@
 // modified, or distributed except according to those terms.
 
 use self::build_builder::Empty;
-use anyhow::{Context, Error, Result};
 use bon::Builder;
 use std::{
     env::{self, VarError},
+    fmt::{self, Debug, Formatter},
+    path::Path,
     str::FromStr,
+    sync::Arc,
 };
-use time::{
-    OffsetDateTime,
-    format_description::{self, well_known::Iso8601},
-};
+use time::OffsetDateTime;
 use vergen_lib::{
-    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, VergenKey,
-    add_default_map_entry, add_map_entry,
-    constants::{BUILD_DATE_NAME, BUILD_TIMESTAMP_NAME, BUILD_TIMESTAMP_UNIX_NAME},
+    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, EntryContext,
+    Error, FromManifest, Granularity, ManifestKeys, TimeFormat, TimeZone, VERGEN_TZ, VergenKey,
+    add_default_map_entry, add_map_entry, in_time_zone,
 };
 
 /// The `VERGEN_BUILD_*` configuration features
@@ -112,6 +111,75 @@ use vergen_lib::{
 /// ```
 ///
 /// # Example
+/// The date and timestamp formats can be configured, i.e. for compact stamps
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::{Build, TimeFormat};
+/// #
+/// # fn main() -> Result<()> {
+/// let build = Build::all()
+///     .date_format(TimeFormat::Description("[year][month][day]".into()))
+///     .timestamp_format(TimeFormat::Compact)
+///     .build();
+/// Emitter::new().add_instructions(&build)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// # Example
+/// The date and timestamp can be rendered in a specific time zone, regardless of where
+/// the build runs.  The `VERGEN_TZ` environment variable, i.e. `VERGEN_TZ=+05:30` or
+/// `VERGEN_TZ=Europe/Berlin`, overrides the configured time zone.
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::{Build, TimeZone};
+/// #
+/// # fn main() -> Result<()> {
+/// let build = Build::all().time_zone(TimeZone::Named("America/New_York".into())).build();
+/// Emitter::new().add_instructions(&build)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// # Example
+/// The date and timestamp can be based off the time of the checked out commit when
+/// `SOURCE_DATE_EPOCH` is not set, so builds of the same commit in a git repository get
+/// the same date and timestamp without any CI setup.  The commit is read by the git
+/// instruction set, i.e. from `vergen-gitcl`.  Packaged sources, i.e. a crate downloaded
+/// from crates.io, carry no commit time, so `SOURCE_DATE_EPOCH` must be set to build
+/// them reproducibly.
+///
+/// ```ignore
+/// let gitcl = Gitcl::all_git();
+/// let build = Build::all().source_date_epoch_from(&gitcl).build();
+/// Emitter::new()
+///     .add_instructions(&build)?
+///     .add_instructions(&gitcl)?
+///     .emit()?;
+/// ```
+///
+/// # Example
+/// The date and timestamp can be rounded down to the start of the minute, hour or day,
+/// so they stay the same for every build within that period and do not defeat artifact
+/// caching, i.e. `sccache`.
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::{Build, Granularity};
+/// #
+/// # fn main() -> Result<()> {
+/// let build = Build::all().round_down(Granularity::Hour).build();
+/// Emitter::new().add_instructions(&build)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// # Example
 /// This feature also recognizes the idempotent flag.
 ///
 /// **NOTE** - `SOURCE_DATE_EPOCH` takes precedence over the idempotent flag. If you
@@ -143,9 +211,12 @@ use vergen_lib::{
 /// cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 /// cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 /// cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+/// cargo:rerun-if-env-changed=VERGEN_TZ
+/// cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+/// cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
 /// ```
 ///
-#[derive(Clone, Copy, Debug, Builder, PartialEq)]
+#[derive(Clone, Debug, Builder, PartialEq)]
 #[allow(clippy::struct_excessive_bools, clippy::struct_field_names)]
 pub struct Build {
     /// Configures the default values.
@@ -165,9 +236,44 @@ pub struct Build {
     /// This is opt-in and is not enabled by [`Build::all_build`].
     #[builder(default = false)]
     build_timestamp_unix: bool,
+    /// The format of the `VERGEN_BUILD_DATE` output, `[year]-[month]-[day]` by default
+    #[builder(default = TimeFormat::Date)]
+    date_format: TimeFormat,
+    /// The format of the `VERGEN_BUILD_TIMESTAMP` output, ISO 8601 by default
+    #[builder(default = TimeFormat::Iso8601)]
+    timestamp_format: TimeFormat,
     /// Enable local offset date/timestamp output
     #[builder(default = false)]
     use_local: bool,
+    /// Render the date/timestamp output in the given time zone, including timestamps
+    /// based off `SOURCE_DATE_EPOCH`.  Takes precedence over `use_local`, and is
+    /// overridden by the `VERGEN_TZ` environment variable.
+    time_zone: Option<TimeZone>,
+    /// Base the date/timestamp output on the time of the commit checked out in the
+    /// repository described by the given git instruction set, i.e. `Gitcl`, `Git2` or
+    /// `Gix`, when `SOURCE_DATE_EPOCH` is not set.  The commit is read when the
+    /// instructions are generated, and failing to read it is handled like any other
+    /// failure to generate them.
+    ///
+    /// Outside of a repository there is no commit time to fall back to.  The
+    /// `.cargo_vcs_info.json` of packaged sources only records the commit SHA, and cargo
+    /// gives every packaged file the same fixed modification time.  Failing to read the
+    /// commit of packaged sources is reported as an invalid `SOURCE_DATE_EPOCH`, which must
+    /// be set instead.
+    ///
+    /// Like `SOURCE_DATE_EPOCH`, this takes precedence over the idempotent flag.
+    #[builder(
+        required,
+        default = None,
+        with = |git: &(impl AddEntries + Clone + Send + Sync + 'static)| {
+            Some(CommitTimeSource(Arc::new(git.clone())))
+        }
+    )]
+    source_date_epoch_from: Option<CommitTimeSource>,
+    /// Round the date/timestamp output down to the start of the minute, hour or day, so
+    /// the `VERGEN_BUILD_*` output only changes once per period.  Only the current time is
+    /// rounded, a time pinned by `SOURCE_DATE_EPOCH` or the commit time is emitted as is.
+    round_down: Option<Granularity>,
 }
 
 impl<S: build_builder::State> BuildBuilder<S> {
@@ -197,94 +303,156 @@ impl Build {
         Self::builder().all()
     }
 
-    fn any(self) -> bool {
+    fn any(&self) -> bool {
         self.build_date || self.build_timestamp || self.build_timestamp_unix
     }
 
     fn add_timestamp_entries(
-        self,
+        &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         let (sde, ts) = match env::var("SOURCE_DATE_EPOCH") {
-            Ok(v) => (
-                true,
-                OffsetDateTime::from_unix_timestamp(i64::from_str(&v)?)?,
-            ),
+            Ok(v) => {
+                let secs = i64::from_str(&v).map_err(Error::invalid_source_date_epoch)?;
+                let ts = OffsetDateTime::from_unix_timestamp(secs)
+                    .map_err(Error::invalid_source_date_epoch)?;
+                (true, ts)
+            }
             Err(VarError::NotPresent) => {
-                if self.use_local {
-                    (false, OffsetDateTime::now_local()?)
+                if let Some(secs) = self.commit_time()? {
+                    (
+                        true,
+                        OffsetDateTime::from_unix_timestamp(secs)
+                            .map_err(Error::invalid_source_date_epoch)?,
+                    )
+                } else if self.use_local && self.time_zone.is_none() {
+                    (
+                        false,
+                        OffsetDateTime::now_local().map_err(Error::time_format)?,
+                    )
                 } else {
                     (false, OffsetDateTime::now_utc())
                 }
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => return Err(Error::invalid_source_date_epoch(e)),
         };
+        let mut ts = in_time_zone(ts, self.time_zone.as_ref())?;
+        if let Some(granularity) = self.round_down.filter(|_| !sde) {
+            ts = granularity.round_down(ts)?;
+        }
 
-        self.add_date_entry(idempotent, sde, &ts, cargo_rustc_env, cargo_warning)?;
-        self.add_timestamp_entry(idempotent, sde, &ts, cargo_rustc_env, cargo_warning)?;
-        self.add_timestamp_unix_entry(idempotent, sde, &ts, cargo_rustc_env, cargo_warning);
+        self.add_date_entry(
+            context,
+            idempotent,
+            sde,
+            &ts,
+            cargo_rustc_env,
+            cargo_warning,
+        )?;
+        self.add_timestamp_entry(
+            context,
+            idempotent,
+            sde,
+            &ts,
+            cargo_rustc_env,
+            cargo_warning,
+        )?;
+        self.add_timestamp_unix_entry(
+            context,
+            idempotent,
+            sde,
+            &ts,
+            cargo_rustc_env,
+            cargo_warning,
+        );
         Ok(())
     }
 
+    /// The time of the commit checked out in the repository described by the git
+    /// instruction set given to `source_date_epoch_from`, if any
+    fn commit_time(&self) -> Result<Option<i64>, Error> {
+        let Some(source) = &self.source_date_epoch_from else {
+            return Ok(None);
+        };
+        source.0.commit_time().map_err(|error| {
+            if is_packaged() {
+                Error::invalid_source_date_epoch(format!(
+                    "packaged sources carry no commit time, set SOURCE_DATE_EPOCH ({error})"
+                ))
+            } else {
+                error
+            }
+        })
+    }
+
     fn add_date_entry(
-        self,
+        &self,
+        context: &EntryContext,
         idempotent: bool,
         source_date_epoch: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.build_date {
-            if let Ok(value) = env::var(BUILD_DATE_NAME) {
+            if let Some(value) = context.env_override(VergenKey::BuildDate) {
                 add_map_entry(VergenKey::BuildDate, value, cargo_rustc_env);
             } else if idempotent && !source_date_epoch {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::BuildDate,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                let format = format_description::parse_borrowed::<1>("[year]-[month]-[day]")?;
-                add_map_entry(VergenKey::BuildDate, ts.format(&format)?, cargo_rustc_env);
+                let date = self
+                    .date_format
+                    .format(ts)
+                    .map_err(|e| e.with_key(VergenKey::BuildDate))?;
+                add_map_entry(VergenKey::BuildDate, date, cargo_rustc_env);
             }
         }
         Ok(())
     }
 
     fn add_timestamp_entry(
-        self,
+        &self,
+        context: &EntryContext,
         idempotent: bool,
         source_date_epoch: bool,
         ts: &OffsetDateTime,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.build_timestamp {
-            if let Ok(value) = env::var(BUILD_TIMESTAMP_NAME) {
+            if let Some(value) = context.env_override(VergenKey::BuildTimestamp) {
                 add_map_entry(VergenKey::BuildTimestamp, value, cargo_rustc_env);
             } else if idempotent && !source_date_epoch {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::BuildTimestamp,
                     cargo_rustc_env,
                     cargo_warning,
                 );
             } else {
-                add_map_entry(
-                    VergenKey::BuildTimestamp,
-                    ts.format(&Iso8601::DEFAULT)?,
-                    cargo_rustc_env,
-                );
+                let timestamp = self
+                    .timestamp_format
+                    .format(ts)
+                    .map_err(|e| e.with_key(VergenKey::BuildTimestamp))?;
+                add_map_entry(VergenKey::BuildTimestamp, timestamp, cargo_rustc_env);
             }
         }
         Ok(())
     }
 
     fn add_timestamp_unix_entry(
-        self,
+        &self,
+        context: &EntryContext,
         idempotent: bool,
         source_date_epoch: bool,
         ts: &OffsetDateTime,
@@ -292,10 +460,11 @@ impl Build {
         cargo_warning: &mut CargoWarning,
     ) {
         if self.build_timestamp_unix {
-            if let Ok(value) = env::var(BUILD_TIMESTAMP_UNIX_NAME) {
+            if let Some(value) = context.env_override(VergenKey::BuildTimestampUnix) {
                 add_map_entry(VergenKey::BuildTimestampUnix, value, cargo_rustc_env);
             } else if idempotent && !source_date_epoch {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::BuildTimestampUnix,
                     cargo_rustc_env,
@@ -312,17 +481,83 @@ impl Build {
     }
 }
 
+/// Whether the package being built was created by `cargo package`, i.e. has a
+/// `.cargo_vcs_info.json`
+fn is_packaged() -> bool {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .is_some_and(|dir| Path::new(&dir).join(".cargo_vcs_info.json").is_file())
+}
+
+/// The git instruction set the commit time is read from, see
+/// [`source_date_epoch_from`](BuildBuilder::source_date_epoch_from)
+#[derive(Clone)]
+struct CommitTimeSource(Arc<dyn AddEntries + Send + Sync>);
+
+impl Debug for CommitTimeSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CommitTimeSource")
+            .field(&self.0.workspace_identity())
+            .finish()
+    }
+}
+
+impl PartialEq for CommitTimeSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl FromManifest for Build {
+    const SECTION: &'static str = "build";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "date",
+            "timestamp",
+            "timestamp_unix",
+            "use_local",
+            "date_format",
+            "timestamp_format",
+            "time_zone",
+            "source_date_epoch_from",
+            "round_down",
+        ])?;
+        let mut build = Self::builder()
+            .build_date(keys.has("date"))
+            .build_timestamp(keys.has("timestamp"))
+            .build_timestamp_unix(keys.contains("timestamp_unix"))
+            .maybe_date_format(keys.time_format("date_format"))
+            .maybe_timestamp_format(keys.time_format("timestamp_format"))
+            .use_local(keys.contains("use_local"))
+            .maybe_time_zone(keys.time_zone("time_zone"))
+            .maybe_round_down(keys.granularity("round_down")?)
+            .build();
+        build.source_date_epoch_from = match keys.value("source_date_epoch_from") {
+            None => None,
+            Some("git") => Some(CommitTimeSource(keys.git().ok_or_else(|| {
+                Error::invalid_config(
+                    "build source_date_epoch_from=git requires the git section to be added first",
+                )
+            })?)),
+            Some(value) => {
+                return Err(keys.invalid_value("source_date_epoch_from", value, "git"));
+            }
+        };
+        Ok(build)
+    }
+}
+
 impl AddEntries for Build {
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.any() {
-            self.add_timestamp_entries(idempotent, cargo_rustc_env, cargo_warning)
-                .with_context(|| "Error adding build timestamp entries")?;
+            self.add_timestamp_entries(context, idempotent, cargo_rustc_env, cargo_warning)?;
         }
         Ok(())
     }
@@ -330,16 +565,17 @@ impl AddEntries for Build {
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{:?}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             if self.build_date {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::BuildDate,
                     cargo_rustc_env_map,
@@ -348,6 +584,7 @@ impl AddEntries for Build {
             }
             if self.build_timestamp {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::BuildTimestamp,
                     cargo_rustc_env_map,
@@ -356,6 +593,7 @@ impl AddEntries for Build {
             }
             if self.build_timestamp_unix {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::BuildTimestampUnix,
                     cargo_rustc_env_map,
@@ -365,6 +603,18 @@ impl AddEntries for Build {
             Ok(())
         }
     }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+
+    fn env_inputs(&self) -> Vec<&'static str> {
+        if self.build_date || self.build_timestamp {
+            vec![VERGEN_TZ]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -373,8 +623,13 @@ mod test {
     use crate::Emitter;
     use anyhow::Result;
     use serial_test::serial;
-    use std::io::Write;
-    use vergen_lib::{CustomInsGen, count_idempotent};
+    use std::{env, fs, io::Write, path::Path};
+    use time::UtcOffset;
+    use vergen_lib::{
+        AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, CustomInsGen,
+        DefaultConfig, EntryContext, Error, FromManifest, Granularity, ManifestKeys, TimeFormat,
+        TimeZone, Transform, VergenKey, count_idempotent,
+    };
 
     #[test]
     #[serial]
@@ -537,6 +792,306 @@ mod test {
         });
     }
 
+    #[derive(Clone)]
+    struct CommitTime(Option<i64>);
+
+    impl AddEntries for CommitTime {
+        fn add_map_entries(
+            &self,
+            _idempotent: bool,
+            _context: &EntryContext,
+            _cargo_rustc_env: &mut CargoRustcEnvMap,
+            _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
+            _cargo_warning: &mut CargoWarning,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn add_default_entries(
+            &self,
+            _config: &DefaultConfig,
+            _context: &EntryContext,
+            _cargo_rustc_env_map: &mut CargoRustcEnvMap,
+            _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
+            _cargo_warning: &mut CargoWarning,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn commit_time(&self) -> Result<Option<i64>, Error> {
+            self.0
+                .map(Some)
+                .ok_or_else(|| Error::not_a_git_repo("no .git"))
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn source_date_epoch_from_works() {
+        temp_env::with_var_unset("SOURCE_DATE_EPOCH", || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all()
+                    .source_date_epoch_from(&CommitTime(Some(1_671_809_360)))
+                    .build();
+                _ = Emitter::new()
+                    .idempotent()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_DATE=2022-12-23\n"));
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=2022-12-23T15:29:20.000000000Z\n"
+                ));
+                assert!(!output.contains("set to default"));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("0"), || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all()
+                    .source_date_epoch_from(&CommitTime(Some(1_671_809_360)))
+                    .build();
+                _ = Emitter::new()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_DATE=1970-01-01\n"));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn source_date_epoch_from_failure_is_handled() {
+        temp_env::with_var_unset("SOURCE_DATE_EPOCH", || {
+            let result = || -> Result<bool, Error> {
+                let mut stdout_buf = vec![];
+                let build = Build::all()
+                    .source_date_epoch_from(&CommitTime(None))
+                    .build();
+                Emitter::new()
+                    .fail_on_error()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)
+            }();
+            assert!(matches!(result, Err(Error::NotAGitRepo { .. })));
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn source_date_epoch_from_packaged_sources_fails() -> Result<()> {
+        let dir = env::temp_dir().join(format!("vergen_build_packaged_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join(".cargo_vcs_info.json"),
+            r#"{"git":{"sha1":"abc"}}"#,
+        )?;
+        temp_env::with_vars(
+            [
+                ("SOURCE_DATE_EPOCH", None),
+                ("CARGO_MANIFEST_DIR", Some(dir.as_os_str())),
+            ],
+            || {
+                let build = Build::all()
+                    .source_date_epoch_from(&CommitTime(None))
+                    .build();
+                let mut stdout_buf = vec![];
+                let result = Emitter::new()
+                    .fail_on_error()
+                    .add_instructions(&build)
+                    .and_then(|emitter| emitter.emit_to(&mut stdout_buf));
+                assert!(
+                    matches!(result, Err(Error::InvalidSourceDateEpoch { .. }))
+                        && result.is_err_and(|e| e.to_string().contains("packaged sources"))
+                );
+                let mut stdout_buf = vec![];
+                let emitted = Emitter::new()
+                    .add_instructions(&build)
+                    .and_then(|emitter| emitter.emit_to(&mut stdout_buf));
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(emitted.is_ok());
+                assert!(!output.contains("VERGEN_BUILD_TIMESTAMP="));
+                assert!(output.contains("cargo:warning=Unable to set VERGEN_BUILD_TIMESTAMP"));
+            },
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn round_down_works() {
+        temp_env::with_vars_unset(["SOURCE_DATE_EPOCH", "VERGEN_TZ"], || {
+            let result = || -> Result<()> {
+                let build = Build::all()
+                    .build_timestamp_unix(true)
+                    .round_down(Granularity::Hour)
+                    .build();
+                let emitter = Emitter::default().add_instructions(&build)?.test_emit();
+                let timestamp = &emitter.cargo_rustc_env_map()[&VergenKey::BuildTimestamp];
+                assert!(timestamp.ends_with(":00:00.000000000Z"));
+                let unix = &emitter.cargo_rustc_env_map()[&VergenKey::BuildTimestampUnix];
+                assert_eq!(0, unix.parse::<i64>()? % 3600);
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("1671809360"), || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all()
+                    .build_timestamp_unix(true)
+                    .round_down(Granularity::Hour)
+                    .build();
+                _ = Emitter::new()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=2022-12-23T15:29:20.000000000Z\n"
+                ));
+                assert!(
+                    output.contains("cargo:rustc-env=VERGEN_BUILD_TIMESTAMP_UNIX=1671809360\n")
+                );
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn time_formats_work() {
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("1671809360"), || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all()
+                    .date_format(TimeFormat::Rfc2822)
+                    .timestamp_format(TimeFormat::Compact)
+                    .build();
+                _ = Emitter::new()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_DATE=Fri, 23 Dec 2022 15:29:20 +0000\n"
+                ));
+                assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=202212231529\n"));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn time_zone_works() {
+        let offset = UtcOffset::from_hms(5, 30, 0).unwrap();
+        let vars = [
+            ("SOURCE_DATE_EPOCH", Some("1671809360")),
+            ("VERGEN_TZ", None),
+        ];
+        temp_env::with_vars(vars, || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all().time_zone(TimeZone::Offset(offset)).build();
+                _ = Emitter::new()
+                    .idempotent()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=2022-12-23T20:59:20.000000000+05:30\n"
+                ));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn vergen_tz_overrides_time_zone() {
+        let offset = UtcOffset::from_hms(5, 30, 0).unwrap();
+        let vars = [
+            ("SOURCE_DATE_EPOCH", Some("1671809360")),
+            ("VERGEN_TZ", Some("-10:00")),
+        ];
+        temp_env::with_vars(vars, || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all().time_zone(TimeZone::Offset(offset)).build();
+                _ = Emitter::new()
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=2022-12-23T05:29:20.000000000-10:00\n"
+                ));
+                assert!(output.contains("cargo:rerun-if-env-changed=VERGEN_TZ\n"));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+        temp_env::with_var("VERGEN_TZ", Some("Not/A_Zone"), || {
+            let build = Build::all_build();
+            let result = Emitter::new()
+                .fail_on_error()
+                .add_instructions(&build)
+                .map(|_| ());
+            assert!(matches!(result, Err(Error::InvalidTimeZone { .. })));
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_time_format_fails() {
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("1671809360"), || {
+            let build = Build::builder()
+                .build_date(true)
+                .date_format(TimeFormat::Description("[bogus]".into()))
+                .build();
+            let result = Emitter::new()
+                .fail_on_error()
+                .add_instructions(&build)
+                .map(|_| ());
+            assert!(matches!(result, Err(Error::TimeFormat { .. })));
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn workspace_shared_works() -> Result<()> {
+        let dir = env::temp_dir().join("vergen_workspace_shared");
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        let emit = |epoch: &str| {
+            temp_env::with_var("SOURCE_DATE_EPOCH", Some(epoch), || -> Result<String> {
+                let mut stdout_buf = vec![];
+                _ = Emitter::new()
+                    .workspace_shared_path(&dir)
+                    .add_instructions(&Build::all_build())?
+                    .emit_to(&mut stdout_buf)?;
+                Ok(String::from_utf8_lossy(&stdout_buf).into_owned())
+            })
+        };
+        let first = emit("1671809360")?;
+        assert!(first.contains("VERGEN_BUILD_DATE=2022-12-23"));
+        // A later build script in the same build reuses the shared output
+        assert_eq!(first, emit("1700000000")?);
+        fs::remove_dir_all(&dir)?;
+        assert!(emit("1700000000")?.contains("VERGEN_BUILD_DATE=2023-11-14"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn build_timestamp_unix_works() {
@@ -558,6 +1113,37 @@ mod test {
         });
     }
 
+    #[test]
+    #[serial]
+    fn build_rust_source_is_typed() {
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("1671809360"), || {
+            let result = || -> Result<()> {
+                let path = env::temp_dir().join("vergen_build_rust_source_is_typed.rs");
+                let build = Build::builder()
+                    .build_date(true)
+                    .build_timestamp_unix(true)
+                    .build();
+                _ = Emitter::new()
+                    .rust_source_path(&path)
+                    .add_instructions(&build)?
+                    .emit_to(&mut vec![])?;
+                let source = fs::read_to_string(&path)?;
+                fs::remove_file(&path)?;
+                assert!(
+                    source.contains("pub const BUILD_DATE: Option<&str> = Some(\"2022-12-23\");")
+                );
+                assert!(source.contains("pub const BUILD_TIMESTAMP: Option<&str> = None;"));
+                assert!(
+                    source.contains(
+                        "pub const BUILD_TIMESTAMP_UNIX: Option<i64> = Some(1671809360);"
+                    )
+                );
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
     #[test]
     #[serial]
     fn build_timestamp_unix_idempotent() -> Result<()> {
@@ -602,7 +1188,7 @@ mod test {
         let source = [0x66, 0x6f, 0x80, 0x6f];
         let os_str = OsStr::from_bytes(&source[..]);
         temp_env::with_var("SOURCE_DATE_EPOCH", Some(os_str), || {
-            let result = || -> Result<bool> {
+            let result = || -> Result<bool, Error> {
                 let mut stdout_buf = vec![];
                 let build = Build::all_build();
                 Emitter::new()
@@ -611,7 +1197,7 @@ mod test {
                     .add_instructions(&build)?
                     .emit_to(&mut stdout_buf)
             }();
-            assert!(result.is_err());
+            assert!(matches!(result, Err(Error::InvalidSourceDateEpoch { .. })));
         });
     }
 
@@ -628,10 +1214,10 @@ mod test {
             let result = || -> Result<bool> {
                 let mut stdout_buf = vec![];
                 let build = Build::all_build();
-                Emitter::new()
+                Ok(Emitter::new()
                     .idempotent()
                     .add_instructions(&build)?
-                    .emit_to(&mut stdout_buf)
+                    .emit_to(&mut stdout_buf)?)
             }();
             assert!(result.is_ok());
         });
@@ -650,11 +1236,11 @@ mod test {
             let result = || -> Result<bool> {
                 let mut stdout_buf = vec![];
                 let build = Build::all_build();
-                Emitter::new()
+                Ok(Emitter::new()
                     .fail_on_error()
                     .idempotent()
                     .add_instructions(&build)?
-                    .emit_to(&mut stdout_buf)
+                    .emit_to(&mut stdout_buf)?)
             }();
             assert!(result.is_err());
         });
@@ -673,10 +1259,10 @@ mod test {
             let result = || -> Result<bool> {
                 let mut stdout_buf = vec![];
                 let build = Build::all_build();
-                Emitter::new()
+                Ok(Emitter::new()
                     .idempotent()
                     .add_instructions(&build)?
-                    .emit_to(&mut stdout_buf)
+                    .emit_to(&mut stdout_buf)?)
             }();
             assert!(result.is_ok());
         });
@@ -745,4 +1331,188 @@ mod test {
         assert_eq!(0, emitter.cargo_warning().len());
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn build_key_prefix_works() {
+        temp_env::with_vars(
+            [
+                ("MYAPP_BUILD_DATE", Some("my date")),
+                ("VERGEN_BUILD_TIMESTAMP", Some("ignored")),
+            ],
+            || {
+                let result = || -> Result<()> {
+                    let mut stdout_buf = vec![];
+                    let build = Build::all().build_timestamp_unix(true).build();
+                    _ = Emitter::default()
+                        .key_prefix("MYAPP")
+                        .rename_key(VergenKey::BuildTimestampUnix, "BUILT_AT")
+                        .add_instructions(&build)?
+                        .emit_to(&mut stdout_buf)?;
+                    let output = String::from_utf8_lossy(&stdout_buf);
+                    assert!(output.contains("cargo:rustc-env=MYAPP_BUILD_DATE=my date\n"));
+                    assert!(output.contains("cargo:rustc-env=MYAPP_BUILD_TIMESTAMP="));
+                    assert!(!output.contains("MYAPP_BUILD_TIMESTAMP=ignored"));
+                    assert!(output.contains("cargo:rustc-env=BUILT_AT="));
+                    assert!(!output.contains("VERGEN_BUILD"));
+                    assert!(output.contains("cargo:rerun-if-env-changed=MYAPP_BUILD_DATE\n"));
+                    assert!(output.contains("cargo:rerun-if-env-changed=BUILT_AT\n"));
+                    Ok(())
+                }();
+                assert!(result.is_ok());
+            },
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn build_transform_works() {
+        temp_env::with_var("SOURCE_DATE_EPOCH", Some("1671809360"), || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let build = Build::all_build();
+                _ = Emitter::default()
+                    .transform(VergenKey::BuildDate, Transform::Truncate(4))
+                    .derived_key("BUILD_YEAR", "year-{VERGEN_BUILD_DATE}")
+                    .add_instructions(&build)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_DATE=2022\n"));
+                assert!(output.contains("cargo:rustc-env=BUILD_YEAR=year-2022\n"));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    fn with_vergen_toml<F>(name: &str, contents: &str, f: F)
+    where
+        F: FnOnce(&Path) -> Result<()>,
+    {
+        let dir = env::temp_dir().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("vergen.toml"), contents).unwrap();
+        temp_env::with_var("CARGO_MANIFEST_DIR", Some(&dir), || {
+            assert!(f(&dir).is_ok());
+        });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn from_manifest_works() {
+        let config = "idempotent = true\nbuild = [\"date\", \"timestamp_unix\"]\n";
+        with_vergen_toml("vergen_from_manifest_works", config, |dir| {
+            let mut stdout_buf = vec![];
+            _ = Emitter::from_manifest()?
+                .add_manifest_instructions::<Build>()?
+                .emit_to(&mut stdout_buf)?;
+            let output = String::from_utf8_lossy(&stdout_buf);
+            assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_DATE=VERGEN_IDEMPOTENT_OUTPUT"));
+            assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_TIMESTAMP_UNIX="));
+            assert!(!output.contains("cargo:rustc-env=VERGEN_BUILD_TIMESTAMP="));
+            let rerun = format!(
+                "cargo:rerun-if-changed={}",
+                dir.join("vergen.toml").display()
+            );
+            assert!(output.contains(&rerun));
+            Ok(())
+        });
+    }
+
+    impl FromManifest for CommitTime {
+        const SECTION: &'static str = "git";
+
+        fn from_manifest(_keys: &ManifestKeys) -> Result<Self, Error> {
+            Ok(CommitTime(Some(1_671_809_360)))
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn from_manifest_options_work() {
+        let config = r#"git = true
+build = [
+    "all",
+    "date_format=[year].[month].[day]",
+    "timestamp_format=rfc3339",
+    "time_zone=+05:30",
+    "source_date_epoch_from=git",
+    "round_down=hour",
+]
+"#;
+        temp_env::with_vars_unset(["SOURCE_DATE_EPOCH", "VERGEN_TZ"], || {
+            with_vergen_toml("vergen_from_manifest_options", config, |_| {
+                let mut stdout_buf = vec![];
+                _ = Emitter::from_manifest()?
+                    .add_manifest_instructions::<CommitTime>()?
+                    .add_manifest_instructions::<Build>()?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains("cargo:rustc-env=VERGEN_BUILD_DATE=2022.12.23\n"));
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_BUILD_TIMESTAMP=2022-12-23T20:59:20+05:30\n"
+                ));
+                Ok(())
+            });
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn from_manifest_invalid_options_fail() {
+        for (name, config) in [
+            (
+                "vergen_from_manifest_no_git",
+                "build = [\"source_date_epoch_from=git\"]\n",
+            ),
+            (
+                "vergen_from_manifest_svn",
+                "build = [\"source_date_epoch_from=svn\"]\n",
+            ),
+            (
+                "vergen_from_manifest_week",
+                "build = [\"round_down=week\"]\n",
+            ),
+        ] {
+            with_vergen_toml(name, config, |_| {
+                let result = Emitter::from_manifest()?
+                    .add_manifest_instructions::<Build>()
+                    .map(|_| ());
+                assert!(matches!(result, Err(Error::InvalidConfig { .. })));
+                Ok(())
+            });
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn from_manifest_invalid_config_fails() {
+        with_vergen_toml("vergen_from_manifest_unknown", "unknown = true\n", |_| {
+            assert!(matches!(
+                Emitter::from_manifest(),
+                Err(Error::InvalidConfig { .. })
+            ));
+            Ok(())
+        });
+        with_vergen_toml("vergen_from_manifest_key", "build = [\"bogus\"]\n", |_| {
+            let result = Emitter::from_manifest()?
+                .add_manifest_instructions::<Build>()
+                .map(|_| ());
+            assert!(matches!(result, Err(Error::InvalidConfig { .. })));
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn from_manifest_missing_instructions_fails() {
+        with_vergen_toml("vergen_from_manifest_missing", "cargo = true\n", |_| {
+            let result = Emitter::from_manifest()?
+                .add_manifest_instructions::<Build>()?
+                .emit_to(&mut vec![]);
+            assert!(matches!(result, Err(Error::InvalidConfig { .. })));
+            Ok(())
+        });
+    }
 }
@@ -10,22 +10,18 @@ use self::cargo_builder::Empty;
 #[cfg(feature = "cargo_metadata")]
 use self::cargo_builder::SetDependencies;
 use self::cargo_builder::{SetDebug, SetFeatures, SetOptLevel, SetTargetTriple};
-#[cfg(feature = "cargo_metadata")]
-use anyhow::anyhow;
-use anyhow::{Error, Result};
 use bon::Builder;
 #[cfg(feature = "cargo_metadata")]
 use cargo_metadata::{DepKindInfo, DependencyKind, MetadataCommand, Package, PackageId};
 #[cfg(feature = "cargo_metadata")]
 use regex::Regex;
 use std::env;
-#[cfg(feature = "cargo_metadata")]
-use vergen_lib::constants::CARGO_DEPENDENCIES;
 use vergen_lib::{
-    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, VergenKey,
-    add_default_map_entry, add_map_entry,
-    constants::{CARGO_DEBUG, CARGO_FEATURES, CARGO_OPT_LEVEL, CARGO_TARGET_TRIPLE},
+    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, EntryContext,
+    Error, FromManifest, ManifestKeys, VergenKey, add_default_map_entry, add_map_entry,
 };
+#[cfg(feature = "cargo_metadata")]
+use vergen_lib::{file_digest, handle_key_failure};
 
 /// Configure the emission of `VERGEN_CARGO_*` instructions
 ///
@@ -250,15 +246,21 @@ impl Cargo {
     fn get_dependencies(
         name_filter: Option<&'static str>,
         dep_kind_filter: Option<DependencyKind>,
-    ) -> Result<String> {
-        let metadata = MetadataCommand::new().exec()?;
-        let resolved_crates = metadata.resolve.ok_or_else(|| anyhow!("No resolve"))?;
-        let root_id = resolved_crates.root.ok_or_else(|| anyhow!("No root id"))?;
+    ) -> Result<String, Error> {
+        let metadata = MetadataCommand::new()
+            .exec()
+            .map_err(Error::cargo_metadata)?;
+        let resolved_crates = metadata
+            .resolve
+            .ok_or_else(|| Error::cargo_metadata("No resolve"))?;
+        let root_id = resolved_crates
+            .root
+            .ok_or_else(|| Error::cargo_metadata("No root id"))?;
         let root = resolved_crates
             .nodes
             .into_iter()
             .find(|node| node.id == root_id)
-            .ok_or_else(|| anyhow!("No root node"))?;
+            .ok_or_else(|| Error::cargo_metadata("No root node"))?;
         let package_ids: Vec<(PackageId, Vec<DepKindInfo>)> = root
             .deps
             .into_iter()
@@ -343,19 +345,39 @@ impl Cargo {
         clippy::unused_self,
         clippy::trivially_copy_pass_by_ref
     )]
-    fn add_dependencies(&self, _cargo_rustc_env: &mut CargoRustcEnvMap) -> Result<()> {
+    fn add_dependencies(
+        &self,
+        _context: &EntryContext,
+        _cargo_rustc_env: &mut CargoRustcEnvMap,
+        _cargo_warning: &mut CargoWarning,
+    ) -> Result<(), Error> {
         Ok(())
     }
 
     #[cfg(feature = "cargo_metadata")]
-    fn add_dependencies(&self, cargo_rustc_env: &mut CargoRustcEnvMap) -> Result<()> {
+    fn add_dependencies(
+        &self,
+        context: &EntryContext,
+        cargo_rustc_env: &mut CargoRustcEnvMap,
+        cargo_warning: &mut CargoWarning,
+    ) -> Result<(), Error> {
         if self.dependencies {
-            if let Ok(value) = env::var(CARGO_DEPENDENCIES) {
+            if let Some(value) = context.env_override(VergenKey::CargoDependencies) {
                 add_map_entry(VergenKey::CargoDependencies, value, cargo_rustc_env);
             } else {
-                let value = Self::get_dependencies(self.name_filter, self.dep_kind_filter)?;
-                if !value.is_empty() {
-                    add_map_entry(VergenKey::CargoDependencies, value, cargo_rustc_env);
+                match Self::get_dependencies(self.name_filter, self.dep_kind_filter) {
+                    Ok(value) => {
+                        if !value.is_empty() {
+                            add_map_entry(VergenKey::CargoDependencies, value, cargo_rustc_env);
+                        }
+                    }
+                    Err(e) => handle_key_failure(
+                        context,
+                        VergenKey::CargoDependencies,
+                        e,
+                        cargo_rustc_env,
+                        cargo_warning,
+                    )?,
                 }
             }
         }
@@ -366,6 +388,7 @@ impl Cargo {
     #[allow(clippy::unused_self, clippy::trivially_copy_pass_by_ref)]
     fn add_default_dependencies(
         &self,
+        _context: &EntryContext,
         _config: &DefaultConfig,
         _cargo_rustc_env_map: &mut CargoRustcEnvMap,
         _cargo_warning: &mut CargoWarning,
@@ -375,12 +398,14 @@ impl Cargo {
     #[cfg(feature = "cargo_metadata")]
     fn add_default_dependencies(
         &self,
+        context: &EntryContext,
         config: &DefaultConfig,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.dependencies {
             add_default_map_entry(
+                context,
                 *config.idempotent(),
                 VergenKey::CargoDependencies,
                 cargo_rustc_env_map,
@@ -390,25 +415,80 @@ impl Cargo {
     }
 }
 
+impl Cargo {
+    #[cfg(not(feature = "cargo_metadata"))]
+    #[allow(clippy::unused_self, clippy::trivially_copy_pass_by_ref)]
+    fn add_dependency_inputs(&self, _inputs: &mut Vec<String>) {}
+
+    #[cfg(feature = "cargo_metadata")]
+    fn add_dependency_inputs(&self, inputs: &mut Vec<String>) {
+        if self.dependencies
+            && let Ok(current_dir) = env::current_dir()
+        {
+            inputs.push(file_digest(&current_dir.join("Cargo.toml")));
+            if let Some(lock_file) = current_dir
+                .ancestors()
+                .map(|dir| dir.join("Cargo.lock"))
+                .find(|lock_file| lock_file.is_file())
+            {
+                inputs.push(file_digest(&lock_file));
+            }
+        }
+    }
+}
+
+/// Read an environment variable cargo sets for build scripts
+fn build_env(name: &str, key: VergenKey) -> Result<String, Error> {
+    env::var(name).map_err(|e| Error::cargo_metadata(format!("{name}: {e}")).with_key(key))
+}
+
+impl FromManifest for Cargo {
+    const SECTION: &'static str = "cargo";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "debug",
+            "features",
+            "opt_level",
+            "target_triple",
+            #[cfg(feature = "cargo_metadata")]
+            "dependencies",
+        ])?;
+        let builder = Self::builder()
+            .debug(keys.has("debug"))
+            .features(keys.has("features"))
+            .opt_level(keys.has("opt_level"))
+            .target_triple(keys.has("target_triple"));
+        #[cfg(feature = "cargo_metadata")]
+        let builder = builder.dependencies(keys.has("dependencies"));
+        Ok(builder.build())
+    }
+}
+
 impl AddEntries for Cargo {
     fn add_map_entries(
         &self,
         _idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
-        _cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+        cargo_warning: &mut CargoWarning,
+    ) -> Result<(), Error> {
         if self.any() {
             if self.debug {
-                if let Ok(value) = env::var(CARGO_DEBUG) {
+                if let Some(value) = context.env_override(VergenKey::CargoDebug) {
                     add_map_entry(VergenKey::CargoDebug, value, cargo_rustc_env);
                 } else {
-                    add_map_entry(VergenKey::CargoDebug, env::var("DEBUG")?, cargo_rustc_env);
+                    add_map_entry(
+                        VergenKey::CargoDebug,
+                        build_env("DEBUG", VergenKey::CargoDebug)?,
+                        cargo_rustc_env,
+                    );
                 }
             }
 
             if self.features {
-                if let Ok(value) = env::var(CARGO_FEATURES) {
+                if let Some(value) = context.env_override(VergenKey::CargoFeatures) {
                     add_map_entry(VergenKey::CargoFeatures, value, cargo_rustc_env);
                 } else {
                     let features: Vec<String> =
@@ -419,30 +499,30 @@ impl AddEntries for Cargo {
             }
 
             if self.opt_level {
-                if let Ok(value) = env::var(CARGO_OPT_LEVEL) {
+                if let Some(value) = context.env_override(VergenKey::CargoOptLevel) {
                     add_map_entry(VergenKey::CargoOptLevel, value, cargo_rustc_env);
                 } else {
                     add_map_entry(
                         VergenKey::CargoOptLevel,
-                        env::var("OPT_LEVEL")?,
+                        build_env("OPT_LEVEL", VergenKey::CargoOptLevel)?,
                         cargo_rustc_env,
                     );
                 }
             }
 
             if self.target_triple {
-                if let Ok(value) = env::var(CARGO_TARGET_TRIPLE) {
+                if let Some(value) = context.env_override(VergenKey::CargoTargetTriple) {
                     add_map_entry(VergenKey::CargoTargetTriple, value, cargo_rustc_env);
                 } else {
                     add_map_entry(
                         VergenKey::CargoTargetTriple,
-                        env::var("TARGET")?,
+                        build_env("TARGET", VergenKey::CargoTargetTriple)?,
                         cargo_rustc_env,
                     );
                 }
             }
 
-            self.add_dependencies(cargo_rustc_env)?;
+            self.add_dependencies(context, cargo_rustc_env, cargo_warning)?;
         }
         Ok(())
     }
@@ -450,16 +530,17 @@ impl AddEntries for Cargo {
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{:?}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             if self.debug {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::CargoDebug,
                     cargo_rustc_env_map,
@@ -468,6 +549,7 @@ impl AddEntries for Cargo {
             }
             if self.features {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::CargoFeatures,
                     cargo_rustc_env_map,
@@ -476,6 +558,7 @@ impl AddEntries for Cargo {
             }
             if self.opt_level {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::CargoOptLevel,
                     cargo_rustc_env_map,
@@ -484,16 +567,27 @@ impl AddEntries for Cargo {
             }
             if self.target_triple {
                 add_default_map_entry(
+                    context,
                     *config.idempotent(),
                     VergenKey::CargoTargetTriple,
                     cargo_rustc_env_map,
                     cargo_warning,
                 );
             }
-            self.add_default_dependencies(config, cargo_rustc_env_map, cargo_warning);
+            self.add_default_dependencies(context, config, cargo_rustc_env_map, cargo_warning);
             Ok(())
         }
     }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        let mut inputs = vec![format!("{self:?}")];
+        for name in ["DEBUG", "OPT_LEVEL", "TARGET"] {
+            inputs.push(format!("{name}={}", env::var(name).unwrap_or_default()));
+        }
+        inputs.extend(env::vars().filter_map(Self::is_cargo_feature));
+        self.add_dependency_inputs(&mut inputs);
+        Some(inputs)
+    }
 }
 
 #[cfg(test)]
@@ -502,9 +596,10 @@ mod test {
     use crate::Emitter;
     use anyhow::Result;
     use serial_test::serial;
-    use std::io::Write;
+    use std::{env, fs, io::Write, path::PathBuf};
     use test_util::{with_cargo_vars, with_cargo_vars_ext};
     use vergen_lib::count_idempotent;
+    use vergen_lib::{CfgKind, CollisionPolicy, Error, FailurePolicy, KeyStatus, VergenKey};
 
     #[test]
     #[serial]
@@ -667,12 +762,17 @@ mod test {
     #[serial]
     fn bad_env_fails() -> Result<()> {
         let cargo = Cargo::all_cargo();
-        assert!(
-            Emitter::default()
-                .fail_on_error()
-                .add_instructions(&cargo)
-                .is_err()
-        );
+        let result = Emitter::default()
+            .fail_on_error()
+            .add_instructions(&cargo)
+            .map(|_| ());
+        assert!(matches!(
+            result,
+            Err(Error::CargoMetadata {
+                key: Some(VergenKey::CargoDebug),
+                ..
+            })
+        ));
         Ok(())
     }
 
@@ -713,6 +813,259 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn duplicate_key_warns() {
+        let result = with_cargo_vars(|| {
+            let mut stdout_buf = vec![];
+            let debug = Cargo::builder().debug(true).build();
+            _ = Emitter::default()
+                .rename_key(VergenKey::CargoOptLevel, "VERGEN_CARGO_DEBUG")
+                .add_instructions(&debug)?
+                .add_instructions(&Cargo::all_cargo())?
+                .emit_to(&mut stdout_buf)?;
+            let output = String::from_utf8_lossy(&stdout_buf);
+            assert!(output.contains(
+                "cargo:warning=duplicate key (VERGEN_CARGO_DEBUG): generated by more than one instruction set\n"
+            ));
+            assert!(output.contains(
+                "cargo:warning=duplicate key (VERGEN_CARGO_OPT_LEVEL): VERGEN_CARGO_DEBUG is also the name of VERGEN_CARGO_DEBUG\n"
+            ));
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn duplicate_key_errors() {
+        let result = with_cargo_vars(|| {
+            let debug = Cargo::builder().debug(true).build();
+            let result = Emitter::default()
+                .collision_policy(CollisionPolicy::Error)
+                .add_instructions(&debug)?
+                .add_concurrent_instructions(&debug)
+                .emit_to(&mut vec![]);
+            assert!(matches!(
+                result,
+                Err(Error::DuplicateKey {
+                    key: Some(VergenKey::CargoDebug),
+                    ..
+                })
+            ));
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn concurrent_output_matches_serial() {
+        let result = with_cargo_vars(|| {
+            let debug = Cargo::builder().debug(true).features(true).build();
+            let opt_level = Cargo::builder().opt_level(true).target_triple(true).build();
+            let mut serial_buf = vec![];
+            _ = Emitter::default()
+                .add_instructions(&debug)?
+                .add_instructions(&opt_level)?
+                .emit_to(&mut serial_buf)?;
+            let mut concurrent_buf = vec![];
+            _ = Emitter::default()
+                .add_concurrent_instructions(&debug)
+                .add_concurrent_instructions(&opt_level)
+                .emit_to(&mut concurrent_buf)?;
+            assert_eq!(serial_buf, concurrent_buf);
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn concurrent_bad_env_fails_on_emit() {
+        let cargo = Cargo::all_cargo();
+        let mut emitter = Emitter::default();
+        _ = emitter.fail_on_error().add_concurrent_instructions(&cargo);
+        let result = emitter.emit_to(&mut vec![]);
+        assert!(matches!(
+            result,
+            Err(Error::CargoMetadata {
+                key: Some(VergenKey::CargoDebug),
+                ..
+            })
+        ));
+        // The sets are generated once, so fixing the environment afterwards doesn't
+        // change the outcome of this emitter
+        let result = with_cargo_vars(|| {
+            assert!(emitter.emit_to(&mut vec![]).is_err());
+            let mut fresh = Emitter::default();
+            _ = fresh.fail_on_error().add_concurrent_instructions(&cargo);
+            let report = fresh.report()?;
+            assert!(
+                report
+                    .get(VergenKey::CargoDebug.name())
+                    .is_some_and(|entry| entry.status() == KeyStatus::Computed)
+            );
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn bad_env_skip_policy() -> Result<()> {
+        let cargo = Cargo::all_cargo();
+        let config = Emitter::default()
+            .fail_on_error()
+            .add_instructions_with_policy(&cargo, FailurePolicy::Skip)?
+            .test_emit();
+        assert_eq!(0, config.cargo_rustc_env_map().len());
+        assert_eq!(0, config.cargo_warning().len());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn bad_env_default_policy() -> Result<()> {
+        let cargo = Cargo::all_cargo();
+        let config = Emitter::default()
+            .fail_on_error()
+            .add_instructions_with_policy(&cargo, FailurePolicy::Default)?
+            .test_emit();
+        #[cfg(feature = "cargo_metadata")]
+        assert_eq!(5, count_idempotent(config.cargo_rustc_env_map()));
+        #[cfg(not(feature = "cargo_metadata"))]
+        assert_eq!(4, count_idempotent(config.cargo_rustc_env_map()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn bad_env_fail_policy() {
+        let cargo = Cargo::all_cargo();
+        let result = Emitter::default()
+            .default_on_error()
+            .add_instructions_with_policy(&cargo, FailurePolicy::Fail)
+            .map(|_| ());
+        assert!(matches!(result, Err(Error::CargoMetadata { .. })));
+    }
+
+    #[test]
+    #[serial]
+    fn bad_env_key_policy() -> Result<()> {
+        let cargo = Cargo::all_cargo();
+        let config = Emitter::default()
+            .fail_on_error()
+            .key_policy(VergenKey::CargoDebug, FailurePolicy::Skip)
+            .add_instructions(&cargo)?
+            .test_emit();
+        assert_eq!(0, config.cargo_rustc_env_map().len());
+        assert_eq!(0, config.cargo_warning().len());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn bad_env_report() -> Result<()> {
+        let cargo = Cargo::all_cargo();
+        let report = Emitter::default().add_instructions(&cargo)?.report()?;
+        let entry = report.get("VERGEN_CARGO_DEBUG").unwrap();
+        assert_eq!(KeyStatus::Missing, entry.status());
+        assert!(entry.value().is_none());
+        assert!(!entry.errors().is_empty());
+        let report = Emitter::default()
+            .add_instructions_with_policy(&cargo, FailurePolicy::Default)?
+            .report()?;
+        let entry = report.get("VERGEN_CARGO_DEBUG").unwrap();
+        assert_eq!(KeyStatus::DefaultedOnError, entry.status());
+        assert_eq!(Some("VERGEN_IDEMPOTENT_OUTPUT"), entry.value());
+        assert!(entry.errors()[0].contains("VERGEN_CARGO_DEBUG"));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn report_works() {
+        let result = with_cargo_vars_ext(&[("VERGEN_CARGO_DEBUG", Some("override"))], || {
+            let cargo = Cargo::all_cargo();
+            let report = Emitter::default().add_instructions(&cargo)?.report()?;
+            let entry = report.get("VERGEN_CARGO_DEBUG").unwrap();
+            assert_eq!(KeyStatus::EnvOverride, entry.status());
+            assert_eq!(Some("override"), entry.value());
+            let entry = report.get("VERGEN_CARGO_TARGET_TRIPLE").unwrap();
+            assert_eq!(KeyStatus::Computed, entry.status());
+            assert!(entry.errors().is_empty());
+            assert!(
+                report
+                    .to_string()
+                    .contains("VERGEN_CARGO_DEBUG: env override = override")
+            );
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn rerun_if_override_changed() {
+        let result = with_cargo_vars(|| {
+            let mut stdout_buf = vec![];
+            let cargo = Cargo::builder().debug(true).build();
+            _ = Emitter::default()
+                .add_instructions(&cargo)?
+                .emit_to(&mut stdout_buf)?;
+            let output = String::from_utf8_lossy(&stdout_buf);
+            assert!(output.contains("cargo:rerun-if-env-changed=VERGEN_CARGO_DEBUG\n"));
+            assert!(!output.contains("cargo:rerun-if-env-changed=VERGEN_CARGO_OPT_LEVEL"));
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn cache_works() {
+        let cache_dir = env::temp_dir().join("vergen_cargo_cache_works");
+        let _res = fs::remove_dir_all(&cache_dir);
+        let emit = |cache_dir: &PathBuf| -> Result<String> {
+            let mut stdout_buf = vec![];
+            let cargo = Cargo::builder().debug(true).build();
+            _ = Emitter::default()
+                .cache_path(cache_dir)
+                .add_instructions(&cargo)?
+                .emit_to(&mut stdout_buf)?;
+            Ok(String::from_utf8_lossy(&stdout_buf).into_owned())
+        };
+        let result = with_cargo_vars(|| {
+            let output = emit(&cache_dir)?;
+            assert!(output.contains("cargo:rustc-env=VERGEN_CARGO_DEBUG=true\n"));
+            let files: Vec<_> = fs::read_dir(&cache_dir)?.collect::<Result<_, _>>()?;
+            assert_eq!(1, files.len());
+
+            // A second run reuses the cached value
+            let cache_file = files[0].path();
+            let cached = fs::read_to_string(&cache_file)?;
+            fs::write(
+                &cache_file,
+                cached.replace("VERGEN_CARGO_DEBUG true", "VERGEN_CARGO_DEBUG cached"),
+            )?;
+            let output = emit(&cache_dir)?;
+            assert!(output.contains("cargo:rustc-env=VERGEN_CARGO_DEBUG=cached\n"));
+            assert!(output.contains("cargo:rerun-if-env-changed=VERGEN_CARGO_DEBUG\n"));
+            Ok(())
+        });
+        assert!(result.is_ok());
+
+        // A changed input invalidates the cached value
+        let result = with_cargo_vars_ext(&[("CARGO_FEATURE_CACHE", Some("1"))], || {
+            let output = emit(&cache_dir)?;
+            assert!(output.contains("cargo:rustc-env=VERGEN_CARGO_DEBUG=true\n"));
+            Ok(())
+        });
+        assert!(result.is_ok());
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
     #[test]
     #[serial]
     fn cargo_debug_override_works() {
@@ -832,4 +1185,28 @@ mod test {
         );
         assert!(result.is_ok());
     }
+
+    #[test]
+    #[serial]
+    fn cargo_rustc_cfg_works() {
+        let result = with_cargo_vars(|| {
+            let mut stdout_buf = vec![];
+            let cargo = Cargo::all_cargo();
+            _ = Emitter::default()
+                .rustc_cfg(VergenKey::CargoDebug, CfgKind::Bool)
+                .rustc_cfg(VergenKey::CargoOptLevel, CfgKind::KeyValue)
+                .add_instructions(&cargo)?
+                .emit_to(&mut stdout_buf)?;
+            let output = String::from_utf8_lossy(&stdout_buf);
+            assert!(output.contains("cargo:rustc-check-cfg=cfg(vergen_cargo_debug)\n"));
+            assert!(output.contains("cargo:rustc-cfg=vergen_cargo_debug\n"));
+            assert!(
+                output
+                    .contains("cargo:rustc-check-cfg=cfg(vergen_cargo_opt_level, values(any()))\n")
+            );
+            assert!(output.contains("cargo:rustc-cfg=vergen_cargo_opt_level=\"1\"\n"));
+            Ok(())
+        });
+        assert!(result.is_ok());
+    }
 }
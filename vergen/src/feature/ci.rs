@@ -0,0 +1,837 @@
+// Copyright (c) 2022 vergen developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use self::ci_builder::Empty;
+use bon::Builder;
+use std::env;
+use vergen_lib::{
+    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, EntryContext,
+    Error, FromManifest, ManifestKeys, VergenKey, add_default_map_entry, add_map_entry,
+};
+
+/// The environment variables the CI metadata is read from
+const ENV_INPUTS: &[&str] = &[
+    // GitHub Actions
+    "GITHUB_ACTIONS",
+    "GITHUB_EVENT_NAME",
+    "GITHUB_JOB",
+    "GITHUB_REF",
+    "GITHUB_REPOSITORY",
+    "GITHUB_RUN_ID",
+    "GITHUB_SERVER_URL",
+    // GitLab CI
+    "GITLAB_CI",
+    "CI_JOB_NAME",
+    "CI_MERGE_REQUEST_IID",
+    "CI_PIPELINE_ID",
+    "CI_PIPELINE_SOURCE",
+    "CI_PIPELINE_URL",
+    // Jenkins
+    "JENKINS_URL",
+    "BUILD_NUMBER",
+    "BUILD_URL",
+    "CHANGE_ID",
+    "JOB_NAME",
+    // Buildkite
+    "BUILDKITE",
+    "BUILDKITE_BUILD_ID",
+    "BUILDKITE_BUILD_URL",
+    "BUILDKITE_LABEL",
+    "BUILDKITE_PULL_REQUEST",
+    "BUILDKITE_SOURCE",
+    // CircleCI
+    "CIRCLECI",
+    "CIRCLE_BUILD_NUM",
+    "CIRCLE_BUILD_URL",
+    "CIRCLE_JOB",
+    "CIRCLE_PULL_REQUEST",
+    // Azure Pipelines
+    "TF_BUILD",
+    "BUILD_BUILDID",
+    "BUILD_REASON",
+    "SYSTEM_COLLECTIONURI",
+    "SYSTEM_JOBDISPLAYNAME",
+    "SYSTEM_PULLREQUEST_PULLREQUESTID",
+    "SYSTEM_PULLREQUEST_PULLREQUESTNUMBER",
+    "SYSTEM_TEAMPROJECT",
+];
+
+/// The `VERGEN_CI_*` configuration features
+///
+/// The CI provider and the metadata of the run are detected from the standard
+/// environment variables of GitHub Actions, GitLab CI, Jenkins, Buildkite, `CircleCI` and
+/// Azure Pipelines.
+///
+/// | Variable | Sample |
+/// | -------  | ------ |
+/// | `VERGEN_CI_PROVIDER` | github-actions |
+/// | `VERGEN_CI_RUN_ID` | 7405982011 |
+/// | `VERGEN_CI_RUN_URL` | <https://github.com/rustyhorde/vergen/actions/runs/7405982011> |
+/// | `VERGEN_CI_JOB_NAME` | test |
+/// | `VERGEN_CI_EVENT` | `pull_request` |
+/// | `VERGEN_CI_PR_NUMBER` | 311 |
+///
+/// **NOTE** - Values that are not available, i.e. all of them outside of CI, or the
+/// pull request number of a push, are not emitted.  Use the
+/// [`option_env!`](std::option_env!) macro to read them.
+///
+/// | Provider | Detected by |
+/// | -------- | ----------- |
+/// | `github-actions` | `GITHUB_ACTIONS=true` |
+/// | `gitlab` | `GITLAB_CI=true` |
+/// | `jenkins` | `JENKINS_URL` |
+/// | `buildkite` | `BUILDKITE=true` |
+/// | `circleci` | `CIRCLECI=true` |
+/// | `azure-pipelines` | `TF_BUILD=True` |
+///
+/// # Example
+/// Emit all of the CI instructions
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::Ci;
+/// #
+/// # fn main() -> Result<()> {
+/// let ci = Ci::all_ci();
+/// Emitter::default().add_instructions(&ci)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// Emit some of the CI instructions
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::Ci;
+/// #
+/// # fn main() -> Result<()> {
+/// let ci = Ci::builder().provider(true).run_url(true).build();
+/// Emitter::default().add_instructions(&ci)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// Override output with your own value
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::Ci;
+/// #
+/// # fn main() -> Result<()> {
+/// temp_env::with_var("VERGEN_CI_PROVIDER", Some("our-own-ci"), || {
+///     let result = || -> Result<()> {
+///         let ci = Ci::all_ci();
+///         Emitter::default().add_instructions(&ci)?.emit()?;
+///         Ok(())
+///     }();
+///     assert!(result.is_ok());
+/// });
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// # Example
+/// This feature also recognizes the idempotent flag.
+///
+/// ```
+/// # use anyhow::Result;
+/// # use vergen::Emitter;
+/// # use vergen::Ci;
+/// #
+/// # fn main() -> Result<()> {
+/// let ci = Ci::all_ci();
+/// Emitter::default().idempotent().add_instructions(&ci)?.emit()?;
+/// #   Ok(())
+/// # }
+/// ```
+///
+/// The above will always generate the following output
+///
+/// ```text
+/// cargo:rustc-env=VERGEN_CI_PROVIDER=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:rustc-env=VERGEN_CI_RUN_ID=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:rustc-env=VERGEN_CI_RUN_URL=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:rustc-env=VERGEN_CI_JOB_NAME=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:rustc-env=VERGEN_CI_EVENT=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:rustc-env=VERGEN_CI_PR_NUMBER=VERGEN_IDEMPOTENT_OUTPUT
+/// cargo:warning=VERGEN_CI_PROVIDER set to default
+/// cargo:warning=VERGEN_CI_RUN_ID set to default
+/// cargo:warning=VERGEN_CI_RUN_URL set to default
+/// cargo:warning=VERGEN_CI_JOB_NAME set to default
+/// cargo:warning=VERGEN_CI_EVENT set to default
+/// cargo:warning=VERGEN_CI_PR_NUMBER set to default
+/// cargo:rerun-if-changed=build.rs
+/// cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
+/// cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
+/// cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+/// cargo:rerun-if-env-changed=BUILDKITE
+/// ...
+/// cargo:rerun-if-env-changed=TF_BUILD
+/// cargo:rerun-if-env-changed=VERGEN_CI_PROVIDER
+/// cargo:rerun-if-env-changed=VERGEN_CI_RUN_ID
+/// cargo:rerun-if-env-changed=VERGEN_CI_RUN_URL
+/// cargo:rerun-if-env-changed=VERGEN_CI_JOB_NAME
+/// cargo:rerun-if-env-changed=VERGEN_CI_EVENT
+/// cargo:rerun-if-env-changed=VERGEN_CI_PR_NUMBER
+/// ```
+///
+#[derive(Builder, Clone, Copy, Debug, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Ci {
+    /// Configures the default values.
+    /// If set to `true` all defaults are in "enabled" state.
+    /// If set to `false` all defaults are in "disabled" state.
+    #[builder(field)]
+    all: bool,
+    /// Enable the CI provider
+    #[builder(default = all)]
+    provider: bool,
+    /// Enable the CI pipeline or workflow run id
+    #[builder(default = all)]
+    run_id: bool,
+    /// Enable the CI pipeline or workflow run URL
+    #[builder(default = all)]
+    run_url: bool,
+    /// Enable the CI job name
+    #[builder(default = all)]
+    job_name: bool,
+    /// Enable the event that triggered the CI run
+    #[builder(default = all)]
+    event: bool,
+    /// Enable the pull request number
+    #[builder(default = all)]
+    pr_number: bool,
+}
+
+impl<S: ci_builder::State> CiBuilder<S> {
+    /// Convenience method that switches the defaults of [`CiBuilder`]
+    /// to enable all of the `VERGEN_CI_*` instructions. It can only be
+    /// called at the start of the building process, i.e. when no config
+    /// has been set yet to avoid overwrites.
+    fn all(mut self) -> Self {
+        self.all = true;
+        self
+    }
+}
+
+/// The metadata of a CI run
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Metadata {
+    provider: Option<&'static str>,
+    run_id: Option<String>,
+    run_url: Option<String>,
+    job_name: Option<String>,
+    event: Option<String>,
+    pr_number: Option<String>,
+}
+
+impl Metadata {
+    /// Detect the CI provider, and read the metadata of the run from its environment
+    fn detect() -> Self {
+        if var("GITHUB_ACTIONS").as_deref() == Some("true") {
+            let run_id = var("GITHUB_RUN_ID");
+            let run_url = match (var("GITHUB_SERVER_URL"), var("GITHUB_REPOSITORY"), &run_id) {
+                (Some(server), Some(repo), Some(id)) => {
+                    Some(format!("{server}/{repo}/actions/runs/{id}"))
+                }
+                _ => None,
+            };
+            // i.e. refs/pull/311/merge
+            let pr_number = var("GITHUB_REF").and_then(|git_ref| {
+                git_ref
+                    .strip_prefix("refs/pull/")
+                    .and_then(|rest| rest.split('/').next())
+                    .map(str::to_string)
+            });
+            Self::new("github-actions", run_id, run_url).job(
+                var("GITHUB_JOB"),
+                var("GITHUB_EVENT_NAME"),
+                pr_number,
+            )
+        } else if var("GITLAB_CI").as_deref() == Some("true") {
+            Self::new("gitlab", var("CI_PIPELINE_ID"), var("CI_PIPELINE_URL")).job(
+                var("CI_JOB_NAME"),
+                var("CI_PIPELINE_SOURCE"),
+                var("CI_MERGE_REQUEST_IID"),
+            )
+        } else if var("JENKINS_URL").is_some() {
+            Self::new("jenkins", var("BUILD_NUMBER"), var("BUILD_URL")).job(
+                var("JOB_NAME"),
+                None,
+                var("CHANGE_ID"),
+            )
+        } else if var("BUILDKITE").as_deref() == Some("true") {
+            Self::new(
+                "buildkite",
+                var("BUILDKITE_BUILD_ID"),
+                var("BUILDKITE_BUILD_URL"),
+            )
+            .job(
+                var("BUILDKITE_LABEL"),
+                var("BUILDKITE_SOURCE"),
+                var("BUILDKITE_PULL_REQUEST"),
+            )
+        } else if var("CIRCLECI").as_deref() == Some("true") {
+            // i.e. https://github.com/rustyhorde/vergen/pull/311
+            let pr_number = var("CIRCLE_PULL_REQUEST")
+                .and_then(|url| url.rsplit('/').next().map(str::to_string));
+            Self::new("circleci", var("CIRCLE_BUILD_NUM"), var("CIRCLE_BUILD_URL")).job(
+                var("CIRCLE_JOB"),
+                None,
+                pr_number,
+            )
+        } else if var("TF_BUILD").is_some_and(|tf_build| tf_build.eq_ignore_ascii_case("true")) {
+            let run_id = var("BUILD_BUILDID");
+            let run_url = match (
+                var("SYSTEM_COLLECTIONURI"),
+                var("SYSTEM_TEAMPROJECT"),
+                &run_id,
+            ) {
+                (Some(collection), Some(project), Some(id)) => Some(format!(
+                    "{}/{project}/_build/results?buildId={id}",
+                    collection.trim_end_matches('/')
+                )),
+                _ => None,
+            };
+            let pr_number = var("SYSTEM_PULLREQUEST_PULLREQUESTNUMBER")
+                .or_else(|| var("SYSTEM_PULLREQUEST_PULLREQUESTID"));
+            Self::new("azure-pipelines", run_id, run_url).job(
+                var("SYSTEM_JOBDISPLAYNAME"),
+                var("BUILD_REASON"),
+                pr_number,
+            )
+        } else {
+            Self::default()
+        }
+    }
+
+    fn new(provider: &'static str, run_id: Option<String>, run_url: Option<String>) -> Self {
+        Self {
+            provider: Some(provider),
+            run_id,
+            run_url,
+            ..Self::default()
+        }
+    }
+
+    fn job(
+        self,
+        job_name: Option<String>,
+        event: Option<String>,
+        pr_number: Option<String>,
+    ) -> Self {
+        Self {
+            job_name,
+            event,
+            // i.e. BUILDKITE_PULL_REQUEST=false for a push
+            pr_number: pr_number.filter(|number| number.parse::<u64>().is_ok()),
+            ..self
+        }
+    }
+}
+
+/// Read a variable, treating an empty value as unset
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+impl Ci {
+    /// Enable all of the `VERGEN_CI_*` options
+    #[must_use]
+    pub fn all_ci() -> Self {
+        Self::builder().all().build()
+    }
+
+    /// Convenience method to setup the builder with all of the `VERGEN_CI_*` instructions on
+    pub fn all() -> CiBuilder<Empty> {
+        Self::builder().all()
+    }
+
+    fn any(self) -> bool {
+        self.provider
+            || self.run_id
+            || self.run_url
+            || self.job_name
+            || self.event
+            || self.pr_number
+    }
+
+    fn enabled(self) -> impl Iterator<Item = VergenKey> {
+        [
+            (self.provider, VergenKey::CiProvider),
+            (self.run_id, VergenKey::CiRunId),
+            (self.run_url, VergenKey::CiRunUrl),
+            (self.job_name, VergenKey::CiJobName),
+            (self.event, VergenKey::CiEvent),
+            (self.pr_number, VergenKey::CiPrNumber),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, key)| enabled.then_some(key))
+    }
+
+    fn add_ci_map_entries(
+        self,
+        context: &EntryContext,
+        idempotent: bool,
+        cargo_rustc_env: &mut CargoRustcEnvMap,
+        cargo_warning: &mut CargoWarning,
+    ) {
+        let run = Metadata::detect();
+        for key in self.enabled() {
+            let value = match key {
+                VergenKey::CiProvider => run.provider.map(str::to_string),
+                VergenKey::CiRunId => run.run_id.clone(),
+                VergenKey::CiRunUrl => run.run_url.clone(),
+                VergenKey::CiJobName => run.job_name.clone(),
+                VergenKey::CiEvent => run.event.clone(),
+                _ => run.pr_number.clone(),
+            };
+            if idempotent || context.env_override(key).is_some() {
+                add_default_map_entry(context, idempotent, key, cargo_rustc_env, cargo_warning);
+            } else if let Some(value) = value {
+                add_map_entry(key, value, cargo_rustc_env);
+            }
+        }
+    }
+}
+
+impl FromManifest for Ci {
+    const SECTION: &'static str = "ci";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "provider",
+            "run_id",
+            "run_url",
+            "job_name",
+            "event",
+            "pr_number",
+        ])?;
+        Ok(Self::builder()
+            .provider(keys.has("provider"))
+            .run_id(keys.has("run_id"))
+            .run_url(keys.has("run_url"))
+            .job_name(keys.has("job_name"))
+            .event(keys.has("event"))
+            .pr_number(keys.has("pr_number"))
+            .build())
+    }
+}
+
+impl AddEntries for Ci {
+    fn add_map_entries(
+        &self,
+        idempotent: bool,
+        context: &EntryContext,
+        cargo_rustc_env: &mut CargoRustcEnvMap,
+        _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
+        cargo_warning: &mut CargoWarning,
+    ) -> Result<(), Error> {
+        if self.any() {
+            self.add_ci_map_entries(context, idempotent, cargo_rustc_env, cargo_warning);
+        }
+        Ok(())
+    }
+
+    fn add_default_entries(
+        &self,
+        config: &DefaultConfig,
+        context: &EntryContext,
+        cargo_rustc_env_map: &mut CargoRustcEnvMap,
+        _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
+        cargo_warning: &mut CargoWarning,
+    ) -> Result<(), Error> {
+        if *config.fail_on_error() {
+            Err(config.error().clone())
+        } else {
+            for key in self.enabled() {
+                add_default_map_entry(
+                    context,
+                    *config.idempotent(),
+                    key,
+                    cargo_rustc_env_map,
+                    cargo_warning,
+                );
+            }
+            Ok(())
+        }
+    }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        let mut inputs = vec![format!("{self:?}")];
+        for name in ENV_INPUTS {
+            inputs.push(format!("{name}={}", env::var(name).unwrap_or_default()));
+        }
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+
+    fn env_inputs(&self) -> Vec<&'static str> {
+        if self.any() {
+            ENV_INPUTS.to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Ci, ENV_INPUTS};
+    use crate::Emitter;
+    use anyhow::Result;
+    use serial_test::serial;
+    use std::io::Write;
+    use vergen_lib::{VergenKey, count_idempotent};
+
+    /// Run the closure with only the given CI variables set
+    fn with_ci_vars<F: FnOnce()>(vars: &[(&str, &str)], f: F) {
+        let vars = ENV_INPUTS
+            .iter()
+            .map(|name| {
+                let value = vars.iter().find(|(var, _)| var == name).map(|(_, v)| *v);
+                (*name, value)
+            })
+            .collect::<Vec<_>>();
+        temp_env::with_vars(vars, f);
+    }
+
+    fn ci_output(ci: Ci) -> Result<Emitter> {
+        let mut emitter = Emitter::default();
+        _ = emitter.add_instructions(&ci)?;
+        Ok(emitter)
+    }
+
+    fn value(emitter: &Emitter, key: VergenKey) -> Option<&str> {
+        emitter.cargo_rustc_env_map().get(&key).map(String::as_str)
+    }
+
+    #[test]
+    #[serial]
+    #[allow(clippy::clone_on_copy, clippy::redundant_clone)]
+    fn ci_clone_works() {
+        let ci = Ci::all_ci();
+        let another = ci.clone();
+        assert_eq!(another, ci);
+    }
+
+    #[test]
+    #[serial]
+    fn ci_debug_works() -> Result<()> {
+        let ci = Ci::all_ci();
+        let mut buf = vec![];
+        write!(buf, "{ci:?}")?;
+        assert_ne!(buf.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn ci_default() {
+        with_ci_vars(&[("GITHUB_ACTIONS", "true")], || {
+            let result = || -> Result<()> {
+                let ci = Ci::builder().build();
+                let emitter = ci_output(ci)?;
+                assert_eq!(0, emitter.cargo_rustc_env_map().len());
+                assert_eq!(0, emitter.cargo_warning().len());
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn no_ci_emits_nothing() {
+        with_ci_vars(&[], || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(0, emitter.cargo_rustc_env_map().len());
+                assert_eq!(0, emitter.cargo_warning().len());
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn github_actions_works() {
+        let vars = [
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "rustyhorde/vergen"),
+            ("GITHUB_RUN_ID", "7405982011"),
+            ("GITHUB_JOB", "test"),
+            ("GITHUB_EVENT_NAME", "pull_request"),
+            ("GITHUB_REF", "refs/pull/311/merge"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(
+                    Some("github-actions"),
+                    value(&emitter, VergenKey::CiProvider)
+                );
+                assert_eq!(Some("7405982011"), value(&emitter, VergenKey::CiRunId));
+                assert_eq!(
+                    Some("https://github.com/rustyhorde/vergen/actions/runs/7405982011"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some("test"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(Some("pull_request"), value(&emitter, VergenKey::CiEvent));
+                assert_eq!(Some("311"), value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn github_actions_push_has_no_pr_number() {
+        let vars = [
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "push"),
+            ("GITHUB_REF", "refs/heads/main"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(Some("push"), value(&emitter, VergenKey::CiEvent));
+                assert_eq!(None, value(&emitter, VergenKey::CiRunUrl));
+                assert_eq!(None, value(&emitter, VergenKey::CiPrNumber));
+                assert_eq!(0, emitter.cargo_warning().len());
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn gitlab_works() {
+        let vars = [
+            ("GITLAB_CI", "true"),
+            ("CI_PIPELINE_ID", "1234"),
+            (
+                "CI_PIPELINE_URL",
+                "https://gitlab.com/group/project/-/pipelines/1234",
+            ),
+            ("CI_JOB_NAME", "build"),
+            ("CI_PIPELINE_SOURCE", "merge_request_event"),
+            ("CI_MERGE_REQUEST_IID", "42"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(Some("gitlab"), value(&emitter, VergenKey::CiProvider));
+                assert_eq!(Some("1234"), value(&emitter, VergenKey::CiRunId));
+                assert_eq!(
+                    Some("https://gitlab.com/group/project/-/pipelines/1234"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some("build"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(
+                    Some("merge_request_event"),
+                    value(&emitter, VergenKey::CiEvent)
+                );
+                assert_eq!(Some("42"), value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn jenkins_works() {
+        let vars = [
+            ("JENKINS_URL", "https://ci.example.com/"),
+            ("BUILD_NUMBER", "17"),
+            ("BUILD_URL", "https://ci.example.com/job/vergen/17/"),
+            ("JOB_NAME", "vergen"),
+            ("CHANGE_ID", "9"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(Some("jenkins"), value(&emitter, VergenKey::CiProvider));
+                assert_eq!(Some("17"), value(&emitter, VergenKey::CiRunId));
+                assert_eq!(
+                    Some("https://ci.example.com/job/vergen/17/"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some("vergen"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(None, value(&emitter, VergenKey::CiEvent));
+                assert_eq!(Some("9"), value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn buildkite_works() {
+        let vars = [
+            ("BUILDKITE", "true"),
+            ("BUILDKITE_BUILD_ID", "f62a1b4d-10f9-4790-bc1c-e2c3a0c80983"),
+            (
+                "BUILDKITE_BUILD_URL",
+                "https://buildkite.com/acme/vergen/builds/1514",
+            ),
+            ("BUILDKITE_LABEL", ":rust: test"),
+            ("BUILDKITE_SOURCE", "webhook"),
+            ("BUILDKITE_PULL_REQUEST", "false"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(Some("buildkite"), value(&emitter, VergenKey::CiProvider));
+                assert_eq!(
+                    Some("f62a1b4d-10f9-4790-bc1c-e2c3a0c80983"),
+                    value(&emitter, VergenKey::CiRunId)
+                );
+                assert_eq!(
+                    Some("https://buildkite.com/acme/vergen/builds/1514"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some(":rust: test"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(Some("webhook"), value(&emitter, VergenKey::CiEvent));
+                assert_eq!(None, value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn circleci_works() {
+        let vars = [
+            ("CIRCLECI", "true"),
+            ("CIRCLE_BUILD_NUM", "88"),
+            ("CIRCLE_BUILD_URL", "https://circleci.com/gh/acme/vergen/88"),
+            ("CIRCLE_JOB", "build"),
+            (
+                "CIRCLE_PULL_REQUEST",
+                "https://github.com/rustyhorde/vergen/pull/311",
+            ),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(Some("circleci"), value(&emitter, VergenKey::CiProvider));
+                assert_eq!(Some("88"), value(&emitter, VergenKey::CiRunId));
+                assert_eq!(
+                    Some("https://circleci.com/gh/acme/vergen/88"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some("build"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(Some("311"), value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn azure_pipelines_works() {
+        let vars = [
+            ("TF_BUILD", "True"),
+            ("BUILD_BUILDID", "555"),
+            ("SYSTEM_COLLECTIONURI", "https://dev.azure.com/acme/"),
+            ("SYSTEM_TEAMPROJECT", "vergen"),
+            ("SYSTEM_JOBDISPLAYNAME", "Build"),
+            ("BUILD_REASON", "PullRequest"),
+            ("SYSTEM_PULLREQUEST_PULLREQUESTID", "73"),
+        ];
+        with_ci_vars(&vars, || {
+            let result = || -> Result<()> {
+                let emitter = ci_output(Ci::all_ci())?;
+                assert_eq!(
+                    Some("azure-pipelines"),
+                    value(&emitter, VergenKey::CiProvider)
+                );
+                assert_eq!(Some("555"), value(&emitter, VergenKey::CiRunId));
+                assert_eq!(
+                    Some("https://dev.azure.com/acme/vergen/_build/results?buildId=555"),
+                    value(&emitter, VergenKey::CiRunUrl)
+                );
+                assert_eq!(Some("Build"), value(&emitter, VergenKey::CiJobName));
+                assert_eq!(Some("PullRequest"), value(&emitter, VergenKey::CiEvent));
+                assert_eq!(Some("73"), value(&emitter, VergenKey::CiPrNumber));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn ci_idempotent() {
+        with_ci_vars(&[("GITLAB_CI", "true")], || {
+            let result = || -> Result<()> {
+                let mut emitter = Emitter::default();
+                _ = emitter.idempotent().add_instructions(&Ci::all_ci())?;
+                assert_eq!(6, count_idempotent(emitter.cargo_rustc_env_map()));
+                assert_eq!(6, emitter.cargo_warning().len());
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn ci_override_works() {
+        with_ci_vars(&[], || {
+            temp_env::with_var("VERGEN_CI_PROVIDER", Some("our-own-ci"), || {
+                let result = || -> Result<()> {
+                    let mut stdout_buf = vec![];
+                    let ci = Ci::builder().provider(true).build();
+                    _ = Emitter::default()
+                        .add_instructions(&ci)?
+                        .emit_to(&mut stdout_buf)?;
+                    let output = String::from_utf8_lossy(&stdout_buf);
+                    assert!(output.contains("cargo:rustc-env=VERGEN_CI_PROVIDER=our-own-ci\n"));
+                    assert!(output.contains("cargo:rerun-if-env-changed=VERGEN_CI_PROVIDER\n"));
+                    Ok(())
+                }();
+                assert!(result.is_ok());
+            });
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn ci_env_inputs_rerun() {
+        with_ci_vars(&[("CIRCLECI", "true"), ("CIRCLE_JOB", "build")], || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                _ = Emitter::default()
+                    .add_instructions(&Ci::all_ci())?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                for name in ENV_INPUTS {
+                    assert!(output.contains(&format!("cargo:rerun-if-env-changed={name}\n")));
+                }
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+}
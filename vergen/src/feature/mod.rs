@@ -10,6 +10,8 @@
 pub(crate) mod build;
 #[cfg(feature = "cargo")]
 pub(crate) mod cargo;
+#[cfg(feature = "ci")]
+pub(crate) mod ci;
 #[cfg(feature = "rustc")]
 pub(crate) mod rustc;
 #[cfg(feature = "si")]
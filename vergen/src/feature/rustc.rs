@@ -7,17 +7,12 @@
 // modified, or distributed except according to those terms.
 
 use self::rustc_builder::Empty;
-use anyhow::{Error, Result};
 use bon::Builder;
 use rustc_version::{Channel, VersionMeta, version_meta};
-use std::env;
+use std::{env, path::PathBuf};
 use vergen_lib::{
-    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, VergenKey,
-    add_default_map_entry, add_map_entry,
-    constants::{
-        RUSTC_CHANNEL_NAME, RUSTC_COMMIT_DATE, RUSTC_COMMIT_HASH, RUSTC_HOST_TRIPLE_NAME,
-        RUSTC_LLVM_VERSION, RUSTC_SEMVER_NAME,
-    },
+    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, EntryContext,
+    Error, FromManifest, ManifestKeys, VergenKey, add_default_map_entry, add_map_entry, file_stamp,
 };
 
 /// The `VERGEN_RUSTC_*` configuration features
@@ -151,18 +146,20 @@ impl Rustc {
     #[cfg(not(test))]
     fn add_rustc_map_entries(
         self,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
-        self.add_rustc_to_map(version_meta(), cargo_rustc_env, cargo_warning)
+    ) -> Result<(), Error> {
+        self.add_rustc_to_map(context, version_meta(), cargo_rustc_env, cargo_warning)
     }
 
     #[cfg(test)]
     fn add_rustc_map_entries(
         self,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         use rustc_version::version_meta_for;
 
         let vm = if let Some(rustc_str) = self.str_to_test {
@@ -170,21 +167,23 @@ impl Rustc {
         } else {
             version_meta()
         };
-        self.add_rustc_to_map(vm, cargo_rustc_env, cargo_warning)
+        self.add_rustc_to_map(context, vm, cargo_rustc_env, cargo_warning)
     }
 
     #[allow(clippy::too_many_lines)]
     fn add_rustc_to_map(
         self,
-        rustc_res: std::result::Result<VersionMeta, rustc_version::Error>,
+        context: &EntryContext,
+        rustc_res: Result<VersionMeta, rustc_version::Error>,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
-        let rustc = rustc_res?;
+    ) -> Result<(), Error> {
+        let rustc = rustc_res.map_err(Error::rustc_version)?;
 
         if self.channel {
-            if let Ok(_value) = env::var(RUSTC_CHANNEL_NAME) {
+            if context.env_override(VergenKey::RustcChannel).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcChannel,
                     cargo_rustc_env,
@@ -202,8 +201,9 @@ impl Rustc {
         }
 
         if self.commit_date {
-            if let Ok(_value) = env::var(RUSTC_COMMIT_DATE) {
+            if context.env_override(VergenKey::RustcCommitDate).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitDate,
                     cargo_rustc_env,
@@ -213,6 +213,7 @@ impl Rustc {
                 add_map_entry(VergenKey::RustcCommitDate, commit_date, cargo_rustc_env);
             } else {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitDate,
                     cargo_rustc_env,
@@ -222,8 +223,9 @@ impl Rustc {
         }
 
         if self.commit_hash {
-            if let Ok(_value) = env::var(RUSTC_COMMIT_HASH) {
+            if context.env_override(VergenKey::RustcCommitHash).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitHash,
                     cargo_rustc_env,
@@ -233,6 +235,7 @@ impl Rustc {
                 add_map_entry(VergenKey::RustcCommitHash, commit_hash, cargo_rustc_env);
             } else {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitHash,
                     cargo_rustc_env,
@@ -242,8 +245,9 @@ impl Rustc {
         }
 
         if self.host_triple {
-            if let Ok(_value) = env::var(RUSTC_HOST_TRIPLE_NAME) {
+            if context.env_override(VergenKey::RustcHostTriple).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcHostTriple,
                     cargo_rustc_env,
@@ -255,8 +259,9 @@ impl Rustc {
         }
 
         if self.llvm_version {
-            if let Ok(_value) = env::var(RUSTC_LLVM_VERSION) {
+            if context.env_override(VergenKey::RustcLlvmVersion).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcLlvmVersion,
                     cargo_rustc_env,
@@ -270,6 +275,7 @@ impl Rustc {
                 );
             } else {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcLlvmVersion,
                     cargo_rustc_env,
@@ -279,8 +285,9 @@ impl Rustc {
         }
 
         if self.semver {
-            if let Ok(_value) = env::var(RUSTC_SEMVER_NAME) {
+            if context.env_override(VergenKey::RustcSemver).is_some() {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcSemver,
                     cargo_rustc_env,
@@ -305,16 +312,40 @@ impl Rustc {
     }
 }
 
+impl FromManifest for Rustc {
+    const SECTION: &'static str = "rustc";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "channel",
+            "commit_date",
+            "commit_hash",
+            "host_triple",
+            "llvm_version",
+            "semver",
+        ])?;
+        Ok(Self::builder()
+            .channel(keys.has("channel"))
+            .commit_date(keys.has("commit_date"))
+            .commit_hash(keys.has("commit_hash"))
+            .host_triple(keys.has("host_triple"))
+            .llvm_version(keys.has("llvm_version"))
+            .semver(keys.has("semver"))
+            .build())
+    }
+}
+
 impl AddEntries for Rustc {
     fn add_map_entries(
         &self,
         _idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.any() {
-            self.add_rustc_map_entries(cargo_rustc_env, cargo_warning)
+            self.add_rustc_map_entries(context, cargo_rustc_env, cargo_warning)
         } else {
             Ok(())
         }
@@ -323,16 +354,17 @@ impl AddEntries for Rustc {
     fn add_default_entries(
         &self,
         config: &DefaultConfig,
+        context: &EntryContext,
         cargo_rustc_env_map: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if *config.fail_on_error() {
-            let error = Error::msg(format!("{:?}", config.error()));
-            Err(error)
+            Err(config.error().clone())
         } else {
             if self.channel {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcChannel,
                     cargo_rustc_env_map,
@@ -341,6 +373,7 @@ impl AddEntries for Rustc {
             }
             if self.commit_date {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitDate,
                     cargo_rustc_env_map,
@@ -349,6 +382,7 @@ impl AddEntries for Rustc {
             }
             if self.commit_hash {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcCommitHash,
                     cargo_rustc_env_map,
@@ -357,6 +391,7 @@ impl AddEntries for Rustc {
             }
             if self.host_triple {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcHostTriple,
                     cargo_rustc_env_map,
@@ -365,6 +400,7 @@ impl AddEntries for Rustc {
             }
             if self.llvm_version {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcLlvmVersion,
                     cargo_rustc_env_map,
@@ -373,6 +409,7 @@ impl AddEntries for Rustc {
             }
             if self.semver {
                 add_default_map_entry(
+                    context,
                     false,
                     VergenKey::RustcSemver,
                     cargo_rustc_env_map,
@@ -383,6 +420,30 @@ impl AddEntries for Rustc {
             Ok(())
         }
     }
+
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        let mut inputs = vec![format!("{self:?}")];
+        for name in ["RUSTC", "RUSTC_WRAPPER", "RUSTUP_TOOLCHAIN"] {
+            inputs.push(format!("{name}={}", env::var(name).unwrap_or_default()));
+        }
+        // The compiler binary changes with i.e. a `rustup update`
+        let rustc = PathBuf::from(env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()));
+        let rustc = if rustc.components().count() > 1 {
+            Some(rustc)
+        } else {
+            env::var_os("PATH").and_then(|path| {
+                env::split_paths(&path)
+                    .map(|dir| dir.join(&rustc))
+                    .find(|rustc| rustc.is_file())
+            })
+        };
+        inputs.extend(rustc.as_deref().map(file_stamp));
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
 }
 
 #[cfg(test)]
@@ -393,7 +454,7 @@ mod test {
     use serial_test::serial;
     use std::io::Write;
     use temp_env::with_var;
-    use vergen_lib::count_idempotent;
+    use vergen_lib::{Error, count_idempotent};
 
     #[test]
     #[serial]
@@ -581,12 +642,11 @@ LLVM version: 15.0.6
     fn rustc_fails_on_bad_input() -> Result<()> {
         let mut rustc = Rustc::all_rustc();
         let _ = rustc.with_rustc_str("a_bad_rustcvv_string");
-        assert!(
-            Emitter::default()
-                .fail_on_error()
-                .add_instructions(&rustc)
-                .is_err()
-        );
+        let result = Emitter::default()
+            .fail_on_error()
+            .add_instructions(&rustc)
+            .map(|_| ());
+        assert!(matches!(result, Err(Error::RustcVersion { .. })));
         Ok(())
     }
 
@@ -7,17 +7,12 @@
 // modified, or distributed except according to those terms.
 
 use self::sysinfo_builder::Empty;
-use anyhow::{Result, anyhow};
 use bon::Builder;
 use std::env;
 use sysinfo::{Cpu, Pid, Process, RefreshKind, System, User, Users, get_current_pid};
 use vergen_lib::{
-    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, VergenKey,
-    add_default_map_entry, add_map_entry,
-    constants::{
-        SYSINFO_CPU_BRAND, SYSINFO_CPU_CORE_COUNT, SYSINFO_CPU_FREQUENCY, SYSINFO_CPU_NAME,
-        SYSINFO_CPU_VENDOR, SYSINFO_MEMORY, SYSINFO_NAME, SYSINFO_OS_VERSION, SYSINFO_USER,
-    },
+    AddEntries, CargoRerunIfChanged, CargoRustcEnvMap, CargoWarning, DefaultConfig, EntryContext,
+    Error, FromManifest, ManifestKeys, VergenKey, add_default_map_entry, add_map_entry,
 };
 
 /// The `VERGEN_SYSINFO_*` configuration features
@@ -151,6 +146,15 @@ use vergen_lib::{
 /// cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 /// cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 /// cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_NAME
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_OS_VERSION
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_USER
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_TOTAL_MEMORY
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_VENDOR
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_CORE_COUNT
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_NAME
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_BRAND
+/// cargo:rerun-if-env-changed=VERGEN_SYSINFO_CPU_FREQUENCY
 /// ```
 ///
 #[derive(Builder, Clone, Copy, Debug, PartialEq)]
@@ -251,6 +255,7 @@ impl Sysinfo {
     }
 
     fn add_sysinfo_map_entry(
+        context: &EntryContext,
         key: VergenKey,
         idempotent: bool,
         value: Option<String>,
@@ -258,24 +263,26 @@ impl Sysinfo {
         cargo_warning: &mut CargoWarning,
     ) {
         if idempotent {
-            add_default_map_entry(idempotent, key, cargo_rustc_env, cargo_warning);
+            add_default_map_entry(context, idempotent, key, cargo_rustc_env, cargo_warning);
         } else if let Some(val) = value {
             add_map_entry(key, val, cargo_rustc_env);
         } else {
-            add_default_map_entry(idempotent, key, cargo_rustc_env, cargo_warning);
+            add_default_map_entry(context, idempotent, key, cargo_rustc_env, cargo_warning);
         }
     }
 
     fn add_sysinfo_name(
         &self,
+        context: &EntryContext,
         _system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.name {
-            if let Ok(_value) = env::var(SYSINFO_NAME) {
+            if context.env_override(VergenKey::SysinfoName).is_some() {
                 add_default_map_entry(
+                    context,
                     idempotent,
                     VergenKey::SysinfoName,
                     cargo_rustc_env,
@@ -283,6 +290,7 @@ impl Sysinfo {
                 );
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoName,
                     idempotent,
                     System::name(),
@@ -295,16 +303,18 @@ impl Sysinfo {
 
     fn add_sysinfo_os_verison(
         &self,
+        context: &EntryContext,
         _system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.os_version {
-            if let Ok(value) = env::var(SYSINFO_OS_VERSION) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoOsVersion) {
                 add_map_entry(VergenKey::SysinfoOsVersion, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoOsVersion,
                     idempotent,
                     System::long_os_version(),
@@ -317,16 +327,18 @@ impl Sysinfo {
 
     fn add_sysinfo_user(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.user {
-            if let Ok(value) = env::var(SYSINFO_USER) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoUser) {
                 add_map_entry(VergenKey::SysinfoUser, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoUser,
                     idempotent,
                     self.get_user(system),
@@ -357,31 +369,33 @@ impl Sysinfo {
 
     #[cfg(not(test))]
     #[allow(clippy::unused_self)]
-    fn get_pid(&self) -> Result<Pid> {
-        get_current_pid().map_err(|e| anyhow!(e.to_string()))
+    fn get_pid(&self) -> Result<Pid, Error> {
+        get_current_pid().map_err(Error::sysinfo_unavailable)
     }
 
     #[cfg(test)]
-    fn get_pid(&self) -> Result<Pid> {
+    fn get_pid(&self) -> Result<Pid, Error> {
         if self.fail_pid {
-            Err(anyhow!("unable to determine pid"))
+            Err(Error::sysinfo_unavailable("unable to determine pid"))
         } else {
-            get_current_pid().map_err(|e| anyhow!(e.to_string()))
+            get_current_pid().map_err(Error::sysinfo_unavailable)
         }
     }
 
     fn add_sysinfo_total_memory(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.memory {
-            if let Ok(value) = env::var(SYSINFO_MEMORY) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoMemory) {
                 add_map_entry(VergenKey::SysinfoMemory, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoMemory,
                     idempotent,
                     Some(Self::suffix(system.total_memory())),
@@ -417,16 +431,18 @@ impl Sysinfo {
 
     fn add_sysinfo_cpu_vendor(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.cpu_vendor {
-            if let Ok(value) = env::var(SYSINFO_CPU_VENDOR) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoCpuVendor) {
                 add_map_entry(VergenKey::SysinfoCpuVendor, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoCpuVendor,
                     idempotent,
                     system
@@ -442,15 +458,17 @@ impl Sysinfo {
 
     fn add_sysinfo_cpu_core_count(
         &self,
+        context: &EntryContext,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.cpu_core_count {
-            if let Ok(value) = env::var(SYSINFO_CPU_CORE_COUNT) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoCpuCoreCount) {
                 add_map_entry(VergenKey::SysinfoCpuCoreCount, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoCpuCoreCount,
                     idempotent,
                     System::physical_core_count().as_ref().map(usize::to_string),
@@ -463,16 +481,18 @@ impl Sysinfo {
 
     fn add_sysinfo_cpu_name(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.cpu_name {
-            if let Ok(value) = env::var(SYSINFO_CPU_NAME) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoCpuName) {
                 add_map_entry(VergenKey::SysinfoCpuName, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoCpuName,
                     idempotent,
                     Some(
@@ -492,16 +512,18 @@ impl Sysinfo {
 
     fn add_sysinfo_cpu_brand(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.cpu_brand {
-            if let Ok(value) = env::var(SYSINFO_CPU_BRAND) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoCpuBrand) {
                 add_map_entry(VergenKey::SysinfoCpuBrand, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoCpuBrand,
                     idempotent,
                     system
@@ -517,16 +539,18 @@ impl Sysinfo {
 
     fn add_sysinfo_cpu_frequency(
         &self,
+        context: &EntryContext,
         system: &System,
         idempotent: bool,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         cargo_warning: &mut CargoWarning,
     ) {
         if self.cpu_frequency {
-            if let Ok(value) = env::var(SYSINFO_CPU_FREQUENCY) {
+            if let Some(value) = context.env_override(VergenKey::SysinfoCpuFrequency) {
                 add_map_entry(VergenKey::SysinfoCpuFrequency, value, cargo_rustc_env);
             } else {
                 Self::add_sysinfo_map_entry(
+                    context,
                     VergenKey::SysinfoCpuFrequency,
                     idempotent,
                     system
@@ -541,26 +565,86 @@ impl Sysinfo {
     }
 }
 
+impl FromManifest for Sysinfo {
+    const SECTION: &'static str = "sysinfo";
+
+    fn from_manifest(keys: &ManifestKeys) -> Result<Self, Error> {
+        keys.check(&[
+            "name",
+            "os_version",
+            "user",
+            "memory",
+            "cpu_vendor",
+            "cpu_core_count",
+            "cpu_name",
+            "cpu_brand",
+            "cpu_frequency",
+        ])?;
+        Ok(Self::builder()
+            .name(keys.has("name"))
+            .os_version(keys.has("os_version"))
+            .user(keys.has("user"))
+            .memory(keys.has("memory"))
+            .cpu_vendor(keys.has("cpu_vendor"))
+            .cpu_core_count(keys.has("cpu_core_count"))
+            .cpu_name(keys.has("cpu_name"))
+            .cpu_brand(keys.has("cpu_brand"))
+            .cpu_frequency(keys.has("cpu_frequency"))
+            .build())
+    }
+}
+
 impl AddEntries for Sysinfo {
     fn add_map_entries(
         &self,
         idempotent: bool,
+        context: &EntryContext,
         cargo_rustc_env: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         if self.any() {
             let system = Self::setup_system(self.refresh_kind);
 
-            self.add_sysinfo_name(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_os_verison(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_user(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_total_memory(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_cpu_vendor(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_cpu_core_count(idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_cpu_name(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_cpu_brand(&system, idempotent, cargo_rustc_env, cargo_warning);
-            self.add_sysinfo_cpu_frequency(&system, idempotent, cargo_rustc_env, cargo_warning);
+            self.add_sysinfo_name(context, &system, idempotent, cargo_rustc_env, cargo_warning);
+            self.add_sysinfo_os_verison(
+                context,
+                &system,
+                idempotent,
+                cargo_rustc_env,
+                cargo_warning,
+            );
+            self.add_sysinfo_user(context, &system, idempotent, cargo_rustc_env, cargo_warning);
+            self.add_sysinfo_total_memory(
+                context,
+                &system,
+                idempotent,
+                cargo_rustc_env,
+                cargo_warning,
+            );
+            self.add_sysinfo_cpu_vendor(
+                context,
+                &system,
+                idempotent,
+                cargo_rustc_env,
+                cargo_warning,
+            );
+            self.add_sysinfo_cpu_core_count(context, idempotent, cargo_rustc_env, cargo_warning);
+            self.add_sysinfo_cpu_name(context, &system, idempotent, cargo_rustc_env, cargo_warning);
+            self.add_sysinfo_cpu_brand(
+                context,
+                &system,
+                idempotent,
+                cargo_rustc_env,
+                cargo_warning,
+            );
+            self.add_sysinfo_cpu_frequency(
+                context,
+                &system,
+                idempotent,
+                cargo_rustc_env,
+                cargo_warning,
+            );
         }
         Ok(())
     }
@@ -569,14 +653,27 @@ impl AddEntries for Sysinfo {
     fn add_default_entries(
         &self,
         _config: &DefaultConfig,
+        _context: &EntryContext,
         _cargo_rustc_env_map: &mut CargoRustcEnvMap,
         _cargo_rerun_if_changed: &mut CargoRerunIfChanged,
         _cargo_warning: &mut CargoWarning,
-    ) -> Result<()> {
+    ) -> Result<(), Error> {
         // currently add_map_entries can't error for sysinfo
         // so this will never be used.
         Ok(())
     }
+    fn cache_inputs(&self) -> Option<Vec<String>> {
+        // The system only changes across a reboot, or for the user, a login
+        let mut inputs = vec![format!("{self:?}"), System::boot_time().to_string()];
+        for name in ["USER", "USERNAME"] {
+            inputs.push(format!("{name}={}", env::var(name).unwrap_or_default()));
+        }
+        Some(inputs)
+    }
+
+    fn workspace_identity(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
 }
 
 #[cfg(test)]
@@ -588,7 +685,7 @@ mod test {
     use std::{collections::BTreeMap, io::Write};
     use sysinfo::{CpuRefreshKind, RefreshKind};
     use temp_env::with_var;
-    use vergen_lib::{VergenKey, count_idempotent};
+    use vergen_lib::{EntryContext, VergenKey, count_idempotent};
 
     const IDEM_COUNT: usize = 0;
     const SYSINFO_COUNT: usize = 9;
@@ -775,6 +872,7 @@ mod test {
         let mut map = BTreeMap::new();
         let mut cargo_warning = vec![];
         Sysinfo::add_sysinfo_map_entry(
+            &EntryContext::default(),
             VergenKey::SysinfoCpuBrand,
             false,
             None,
@@ -896,6 +994,32 @@ mod test {
         });
     }
 
+    #[cfg(feature = "redact")]
+    #[test]
+    #[serial]
+    fn sysinfo_redaction_works() {
+        use vergen_lib::Redaction;
+        with_var("VERGEN_SYSINFO_USER", Some("me"), || {
+            let result = || -> Result<()> {
+                let mut stdout_buf = vec![];
+                let si = Sysinfo::all_sysinfo();
+                let key = "salt".to_string();
+                let _failed = Emitter::default()
+                    .redact(VergenKey::SysinfoUser, Redaction::Hmac { key })
+                    .redact(VergenKey::SysinfoName, Redaction::Drop)
+                    .add_instructions(&si)?
+                    .emit_to(&mut stdout_buf)?;
+                let output = String::from_utf8_lossy(&stdout_buf);
+                assert!(output.contains(
+                    "cargo:rustc-env=VERGEN_SYSINFO_USER=hmac-sha256:9dca01c64b87094962067eb750bf48c879eb0a88485b5d19128950cc1a5c6feb"
+                ));
+                assert!(!output.contains("cargo:rustc-env=VERGEN_SYSINFO_NAME="));
+                Ok(())
+            }();
+            assert!(result.is_ok());
+        });
+    }
+
     #[test]
     #[serial]
     fn sysinfo_total_memory_override_works() {
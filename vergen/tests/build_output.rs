@@ -50,6 +50,9 @@ cargo:rerun-if-changed=build.rs
 cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+cargo:rerun-if-env-changed=VERGEN_TZ
+cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
 ";
 
     const IDEM_OUTPUT_CUSTOM_BUILDRS: &str = r"cargo:rustc-env=VERGEN_BUILD_DATE=VERGEN_IDEMPOTENT_OUTPUT
@@ -60,6 +63,9 @@ cargo:rerun-if-changed=a/custom_build.rs
 cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+cargo:rerun-if-env-changed=VERGEN_TZ
+cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
 ";
 
     const SOURCE_DATE_EPOCH_IDEM_OUTPUT: &str = r"cargo:rustc-env=VERGEN_BUILD_DATE=2022-12-23
@@ -68,6 +74,9 @@ cargo:rerun-if-changed=build.rs
 cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+cargo:rerun-if-env-changed=VERGEN_TZ
+cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
 ";
 
     const QUIET_IDEM_OUTPUT: &str = r"cargo:rustc-env=VERGEN_BUILD_DATE=VERGEN_IDEMPOTENT_OUTPUT
@@ -76,6 +85,9 @@ cargo:rerun-if-changed=build.rs
 cargo:rerun-if-env-changed=VERGEN_IDEMPOTENT
 cargo:rerun-if-env-changed=VERGEN_DEFAULT_ON_ERROR
 cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH
+cargo:rerun-if-env-changed=VERGEN_TZ
+cargo:rerun-if-env-changed=VERGEN_BUILD_DATE
+cargo:rerun-if-env-changed=VERGEN_BUILD_TIMESTAMP
 ";
 
     #[test]
@@ -0,0 +1,64 @@
+#[cfg(feature = "ci")]
+mod test_ci {
+    use anyhow::Result;
+    use vergen::{Ci, Emitter};
+
+    const GITHUB_ACTIONS_OUTPUT: &str = r"cargo:rustc-env=VERGEN_CI_PROVIDER=github-actions
+cargo:rustc-env=VERGEN_CI_RUN_ID=7405982011
+cargo:rustc-env=VERGEN_CI_RUN_URL=https://github.com/rustyhorde/vergen/actions/runs/7405982011
+cargo:rustc-env=VERGEN_CI_JOB_NAME=test
+cargo:rustc-env=VERGEN_CI_EVENT=pull_request
+cargo:rustc-env=VERGEN_CI_PR_NUMBER=311
+";
+
+    const IDEMPOTENT_OUTPUT: &str = r"cargo:rustc-env=VERGEN_CI_PROVIDER=VERGEN_IDEMPOTENT_OUTPUT
+cargo:rustc-env=VERGEN_CI_RUN_ID=VERGEN_IDEMPOTENT_OUTPUT
+cargo:rustc-env=VERGEN_CI_RUN_URL=VERGEN_IDEMPOTENT_OUTPUT
+cargo:rustc-env=VERGEN_CI_JOB_NAME=VERGEN_IDEMPOTENT_OUTPUT
+cargo:rustc-env=VERGEN_CI_EVENT=VERGEN_IDEMPOTENT_OUTPUT
+cargo:rustc-env=VERGEN_CI_PR_NUMBER=VERGEN_IDEMPOTENT_OUTPUT
+";
+
+    #[test]
+    fn ci_all_output() {
+        temp_env::with_vars(
+            [
+                ("GITHUB_ACTIONS", Some("true")),
+                ("GITHUB_RUN_ID", Some("7405982011")),
+                ("GITHUB_SERVER_URL", Some("https://github.com")),
+                ("GITHUB_REPOSITORY", Some("rustyhorde/vergen")),
+                ("GITHUB_JOB", Some("test")),
+                ("GITHUB_EVENT_NAME", Some("pull_request")),
+                ("GITHUB_REF", Some("refs/pull/311/merge")),
+            ],
+            || {
+                let result = || -> Result<()> {
+                    let mut stdout_buf = vec![];
+                    let ci = Ci::all_ci();
+                    Emitter::default()
+                        .add_instructions(&ci)?
+                        .emit_to(&mut stdout_buf)?;
+                    let output = String::from_utf8_lossy(&stdout_buf);
+                    assert!(output.starts_with(GITHUB_ACTIONS_OUTPUT));
+                    assert!(output.contains("cargo:rerun-if-env-changed=GITHUB_RUN_ID\n"));
+                    assert!(output.contains("cargo:rerun-if-env-changed=VERGEN_CI_RUN_ID\n"));
+                    Ok(())
+                }();
+                assert!(result.is_ok());
+            },
+        );
+    }
+
+    #[test]
+    fn ci_all_idempotent_output() -> Result<()> {
+        let mut stdout_buf = vec![];
+        let ci = Ci::all_ci();
+        Emitter::default()
+            .idempotent()
+            .add_instructions(&ci)?
+            .emit_to(&mut stdout_buf)?;
+        let output = String::from_utf8_lossy(&stdout_buf);
+        assert!(output.starts_with(IDEMPOTENT_OUTPUT));
+        Ok(())
+    }
+}